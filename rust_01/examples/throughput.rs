@@ -0,0 +1,123 @@
+//! Débit du comptage : l'ancien chemin (regex + `String` par mot dans une
+//! `HashMap`) contre `WordCounter::feed` (scanner d'octets + internement).
+//!
+//! ```text
+//! cargo run --release --example throughput [FILE] [--ignore-case]
+//! ```
+//!
+//! Sans fichier, un corpus synthétique d'environ 32 Mo est généré.
+
+use regex::Regex;
+use std::collections::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use wordfreq::{Tokenizer, WordCounter};
+
+const ROUNDS: usize = 5;
+
+/// Corpus reproductible : vocabulaire de Zipf, un peu de ponctuation,
+/// d'accents et de citations
+fn synthetic(size: usize) -> String {
+    let syllables = [
+        "la", "ter", "mo", "vi", "ré", "pon", "da", "sé", "ku", "in", "ro", "ça",
+    ];
+    let vocabulary: Vec<String> = (0..20_000u64)
+        .map(|i| {
+            let mut h = i.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+            let mut w = String::new();
+            for _ in 0..1 + h % 4 {
+                w.push_str(syllables[(h >> 8) as usize % syllables.len()]);
+                h = h.rotate_left(17).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            }
+            if i % 7 == 0 { w.to_uppercase() } else { w }
+        })
+        .collect();
+
+    let mut text = String::with_capacity(size + 64);
+    let mut state = 42u64;
+    let mut n = 0usize;
+    while text.len() < size {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        // rang ~ Zipf : les premiers mots reviennent beaucoup plus souvent
+        let u = (state >> 11) as f64 / (1u64 << 53) as f64;
+        let rank = ((vocabulary.len() as f64).powf(u) as usize).saturating_sub(1);
+        text.push_str(&vocabulary[rank]);
+        n += 1;
+        text.push_str(match n % 23 {
+            0 => ".\n",
+            5 => ", ",
+            11 => " \"cité\" ",
+            _ => " ",
+        });
+    }
+    text
+}
+
+/// Meilleur temps sur `ROUNDS` passes
+fn best<T>(mut f: impl FnMut() -> T) -> (Duration, T) {
+    let mut best = Duration::MAX;
+    let mut out = None;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        let r = black_box(f());
+        best = best.min(start.elapsed());
+        out = Some(r);
+    }
+    (best, out.expect("at least one round"))
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let ignore_case = args.iter().any(|a| a == "--ignore-case");
+    let text = match args.iter().find(|a| !a.starts_with("--")) {
+        Some(path) => std::fs::read_to_string(path).unwrap_or_else(|e| {
+            eprintln!("{path}: {e}");
+            std::process::exit(1);
+        }),
+        None => synthetic(32 << 20),
+    };
+    let mb = text.len() as f64 / (1024.0 * 1024.0);
+
+    let re = Regex::new(r#""[^"]+"|'[^']+'|\w+"#).expect("valid regex");
+    let (legacy, counts) = best(|| {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for m in re.find_iter(&text) {
+            let w = if ignore_case {
+                m.as_str().to_lowercase()
+            } else {
+                m.as_str().to_string()
+            };
+            *counts.entry(w).or_insert(0) += 1;
+        }
+        counts
+    });
+
+    let tokenizer = Tokenizer::new().ignore_case(ignore_case);
+    let (current, counter) = best(|| {
+        let mut counter = WordCounter::with_tokenizer(tokenizer.clone());
+        counter.feed(&text);
+        counter
+    });
+
+    assert_eq!(counts.len(), counter.unique(), "both paths must agree");
+    assert_eq!(counts.values().sum::<usize>(), counter.total());
+
+    println!(
+        "{mb:.1} MB, {} words, {} distinct",
+        counter.total(),
+        counter.unique()
+    );
+    for (name, t) in [("regex + String", legacy), ("scanner + interner", current)] {
+        println!(
+            "{name:<20} {:>8.1} MB/s  ({:.0} ms)",
+            mb / t.as_secs_f64(),
+            t.as_secs_f64() * 1000.0
+        );
+    }
+    println!(
+        "speedup              {:>8.2}x",
+        legacy.as_secs_f64() / current.as_secs_f64()
+    );
+}
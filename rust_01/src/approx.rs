@@ -0,0 +1,344 @@
+//! Comptage approximatif des mots les plus fréquents en mémoire bornée
+//! (`--approx`), avec l'algorithme Space-Saving (Metwally et al., 2005).
+//!
+//! Les mots suivis sont limités en nombre (`new`) ou en octets (`with_memory`,
+//! taille réelle de chaque mot comprise). Quand un mot inconnu arrive alors
+//! que tout est plein, il remplace le mot de plus petit compteur et hérite de
+//! ce compteur (+1) : son estimation peut donc être trop haute d'au plus
+//! `error`, mais jamais trop basse.
+
+use crate::tokenizer::Tokenizer;
+use std::collections::{BTreeSet, HashMap};
+use std::io::{self, BufRead, Read};
+use std::rc::Rc;
+
+/// Coût mémoire estimé d'un mot suivi, en plus des octets du mot lui-même :
+/// entrée de la table, compteurs, nœud de l'index trié, en-tête du `Rc`
+const ENTRY_OVERHEAD: usize = 96;
+
+/// Les mots plus longs (une citation peut faire toute une ligne) sont
+/// tronqués à cette taille : deux mots de même début sont alors confondus
+pub const MAX_WORD_BYTES: usize = 256;
+
+/// Taille maximale d'un morceau lu par [`SpaceSaving::feed_reader`] : une
+/// entrée sans fin de ligne n'est jamais gardée en entier
+const MAX_CHUNK_BYTES: u64 = 1 << 20;
+
+#[derive(Debug, Clone)]
+struct Slot {
+    /// partagé avec la clé de `index` : le mot n'est stocké qu'une fois
+    word: Rc<str>,
+    count: usize,
+    error: usize,
+}
+
+/// Estimation de fréquence d'un mot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Estimate<'a> {
+    pub word: &'a str,
+    /// borne haute du nombre réel d'occurrences
+    pub count: usize,
+    /// surestimation maximale : le vrai compte est dans `count - error ..= count`
+    pub error: usize,
+}
+
+impl Estimate<'_> {
+    /// Nombre d'occurrences garanti
+    pub fn lower_bound(&self) -> usize {
+        self.count - self.error
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SpaceSaving {
+    capacity: usize,
+    /// budget en octets (mots + `ENTRY_OVERHEAD` par mot)
+    memory: usize,
+    used: usize,
+    index: HashMap<Rc<str>, usize>,
+    /// mots suivis ; les places libérées par une éviction sont réutilisées
+    slots: Vec<Option<Slot>>,
+    free: Vec<usize>,
+    /// (compteur, place) pour retrouver le plus petit compteur sans allouer
+    by_count: BTreeSet<(usize, usize)>,
+    /// plus grand compteur évincé : borne du vrai compte de tout mot non
+    /// suivi, donc compte de départ d'un nouveau mot
+    evicted: usize,
+    total: usize,
+}
+
+impl SpaceSaving {
+    /// Suit au plus `capacity` mots distincts (au moins 1)
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            memory: usize::MAX,
+            used: 0,
+            index: HashMap::new(),
+            slots: Vec::new(),
+            free: Vec::new(),
+            by_count: BTreeSet::new(),
+            evicted: 0,
+            total: 0,
+        }
+    }
+
+    /// Autant de mots que `bytes` le permet, en comptant la taille réelle
+    /// de chaque mot
+    pub fn with_memory(bytes: usize) -> Self {
+        Self {
+            memory: bytes,
+            ..Self::new(usize::MAX)
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Nombre de mots suivis
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Octets comptés contre le budget
+    pub fn memory_used(&self) -> usize {
+        self.used
+    }
+
+    /// Nombre total de mots vus, y compris ceux qui ne sont plus suivis
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Découpe `text` et ajoute ses mots
+    pub fn feed(&mut self, tokenizer: &Tokenizer, text: &str) {
+        for w in tokenizer.words(text) {
+            self.add(&w);
+        }
+    }
+
+    /// Lit `reader` ligne à ligne (en UTF-8, octets invalides remplacés) et
+    /// ajoute ses mots. Une ligne de plus de 1 Mio est découpée au dernier
+    /// blanc : la mémoire utilisée ne dépend pas de la longueur des lignes.
+    pub fn feed_reader(
+        &mut self,
+        tokenizer: &Tokenizer,
+        reader: &mut dyn BufRead,
+    ) -> io::Result<()> {
+        let mut buf = Vec::new();
+        let mut carry = Vec::new();
+        loop {
+            buf.clear();
+            buf.append(&mut carry);
+            let limit = MAX_CHUNK_BYTES.saturating_sub(buf.len() as u64).max(1);
+            let n = Read::take(&mut *reader, limit).read_until(b'\n', &mut buf)?;
+            if buf.is_empty() {
+                return Ok(());
+            }
+            if n > 0 && !buf.ends_with(b"\n") && buf.len() as u64 >= MAX_CHUNK_BYTES {
+                // morceau plein au milieu d'une ligne : la fin du dernier mot
+                // est gardée pour le morceau suivant
+                if let Some(i) = buf.iter().rposition(u8::is_ascii_whitespace) {
+                    carry.extend_from_slice(&buf[i + 1..]);
+                    buf.truncate(i + 1);
+                }
+            }
+            self.feed(tokenizer, &String::from_utf8_lossy(&buf));
+        }
+    }
+
+    /// Ajoute une occurrence d'un mot déjà normalisé
+    pub fn add(&mut self, word: &str) {
+        self.total += 1;
+        let word = truncate(word, MAX_WORD_BYTES);
+
+        if let Some(&id) = self.index.get(word) {
+            let slot = self.slots[id].as_mut().expect("indexed slot");
+            self.by_count.remove(&(slot.count, id));
+            slot.count += 1;
+            self.by_count.insert((slot.count, id));
+            return;
+        }
+
+        let cost = word.len() + ENTRY_OVERHEAD;
+        while !self.index.is_empty()
+            && (self.index.len() >= self.capacity || self.used + cost > self.memory)
+        {
+            self.evict_min();
+        }
+
+        let word: Rc<str> = Rc::from(word);
+        let slot = Slot {
+            word: Rc::clone(&word),
+            count: self.evicted + 1,
+            error: self.evicted,
+        };
+        let id = match self.free.pop() {
+            Some(id) => {
+                self.slots[id] = Some(slot);
+                id
+            }
+            None => {
+                self.slots.push(Some(slot));
+                self.slots.len() - 1
+            }
+        };
+        self.by_count.insert((self.evicted + 1, id));
+        self.index.insert(word, id);
+        self.used += cost;
+    }
+
+    fn evict_min(&mut self) {
+        let Some((count, id)) = self.by_count.pop_first() else {
+            return;
+        };
+        let slot = self.slots[id].take().expect("indexed slot");
+        self.index.remove(&slot.word);
+        self.free.push(id);
+        self.used -= slot.word.len() + ENTRY_OVERHEAD;
+        self.evicted = self.evicted.max(count);
+    }
+
+    /// Surestimation maximale de n'importe quel mot : 0 tant que rien n'a
+    /// été évincé, sinon le plus grand compteur évincé
+    pub fn max_error(&self) -> usize {
+        self.evicted
+    }
+
+    /// Les `n` mots d'estimation la plus haute
+    pub fn top(&self, n: usize) -> Vec<Estimate<'_>> {
+        let mut items: Vec<Estimate> = self
+            .slots
+            .iter()
+            .flatten()
+            .map(|s| Estimate {
+                word: &s.word,
+                count: s.count,
+                error: s.error,
+            })
+            .collect();
+        items.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.word.cmp(b.word)));
+        items.truncate(n);
+        items
+    }
+}
+
+/// Les `max` premiers octets de `s`, sans couper de caractère
+fn truncate(s: &str, max: usize) -> &str {
+    if s.len() <= max {
+        return s;
+    }
+    let mut end = max;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+/// Lit une taille mémoire : "4096", "512K", "64M", "1G" (puissances de 1024)
+pub fn parse_size(s: &str) -> Result<usize, String> {
+    let s = s.trim();
+    let (digits, unit) = match s.find(|c: char| !c.is_ascii_digit()) {
+        Some(i) => s.split_at(i),
+        None => (s, ""),
+    };
+    let shift = match unit.to_ascii_uppercase().trim_end_matches(['B', 'I']) {
+        "" => 0,
+        "K" => 10,
+        "M" => 20,
+        "G" => 30,
+        _ => return Err(format!("invalid size '{s}' (expected e.g. 512K, 64M, 1G)")),
+    };
+    digits
+        .parse::<usize>()
+        .ok()
+        .and_then(|n| n.checked_mul(1 << shift))
+        .ok_or_else(|| format!("invalid size '{s}' (expected e.g. 512K, 64M, 1G)"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_while_under_capacity() {
+        let mut ss = SpaceSaving::new(10);
+        ss.feed(&Tokenizer::new(), "a b a c a b");
+
+        assert_eq!(ss.max_error(), 0);
+        let top = ss.top(2);
+        assert_eq!((top[0].word, top[0].count, top[0].error), ("a", 3, 0));
+        assert_eq!((top[1].word, top[1].count, top[1].error), ("b", 2, 0));
+    }
+
+    #[test]
+    fn heavy_hitters_survive_unique_ids() {
+        let mut ss = SpaceSaving::new(8);
+        let tok = Tokenizer::new();
+        for i in 0..1000 {
+            ss.feed(&tok, &format!("error id{i}"));
+            if i % 4 == 0 {
+                ss.feed(&tok, "timeout");
+            }
+        }
+
+        assert_eq!(ss.total(), 2250);
+        let top = ss.top(2);
+        assert_eq!(top[0].word, "error");
+        assert_eq!(top[1].word, "timeout");
+        // le vrai compte est toujours dans l'intervalle annoncé
+        assert!(top[0].lower_bound() <= 1000 && 1000 <= top[0].count);
+        assert!(top[1].lower_bound() <= 250 && 250 <= top[1].count);
+        assert!(ss.max_error() <= ss.total() / ss.capacity());
+    }
+
+    #[test]
+    fn memory_budget_counts_word_bytes() {
+        let budget = 450;
+        let mut ss = SpaceSaving::with_memory(budget);
+        ss.feed(&Tokenizer::new(), "a a a b b c");
+        assert_eq!(ss.len(), 3);
+
+        // un long mot prend la place de plusieurs petits : "c" et "b" sortent
+        let long = "x".repeat(200);
+        ss.add(&long);
+        assert_eq!(ss.len(), 2);
+        assert!(ss.memory_used() <= budget);
+        assert_eq!(ss.top(1)[0].word, "a");
+        assert_eq!(ss.max_error(), 2);
+
+        // au-delà de MAX_WORD_BYTES, le mot est tronqué
+        ss.add(&"y".repeat(10 * MAX_WORD_BYTES));
+        assert!(ss.top(10).iter().all(|e| e.word.len() <= MAX_WORD_BYTES));
+        assert!(ss.memory_used() <= budget);
+    }
+
+    #[test]
+    fn long_lines_are_read_in_chunks() {
+        // 3 Mio sans fin de ligne : lu par morceaux, aucun mot coupé
+        let text = "lorem ipsum ".repeat(3 << 20 >> 4);
+        let mut ss = SpaceSaving::new(16);
+        ss.feed_reader(&Tokenizer::new(), &mut text.as_bytes())
+            .unwrap();
+
+        let top = ss.top(3);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].count, text.len() / 12);
+        assert_eq!(top[1].count, text.len() / 12);
+    }
+
+    #[test]
+    fn sizes() {
+        assert_eq!(parse_size("4096"), Ok(4096));
+        assert_eq!(parse_size("512K"), Ok(512 * 1024));
+        assert_eq!(parse_size("64mb"), Ok(64 << 20));
+        assert_eq!(parse_size("1GiB"), Ok(1 << 30));
+        assert!(parse_size("12Q").is_err());
+        assert!(parse_size("M").is_err());
+    }
+}
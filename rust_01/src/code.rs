@@ -0,0 +1,379 @@
+//! Mode `--code` : identifiants de code source découpés en sous-mots
+//! (camelCase, PascalCase, snake_case, SCREAMING_CASE). Les mots-clés sont
+//! ignorés quand le langage est connu (`--code-lang` ou extension du
+//! fichier) : `map` est un mot-clé en Go, pas en Rust.
+
+use std::ops::Range;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Ce que `--code` compte pour chaque identifiant
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CodeMode {
+    /// Les sous-mots : `parseHttpRequest` → parse, Http, Request
+    #[default]
+    Split,
+    /// L'identifiant entier
+    Whole,
+    /// L'identifiant entier et ses sous-mots
+    Both,
+}
+
+impl FromStr for CodeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "split" => Ok(CodeMode::Split),
+            "whole" => Ok(CodeMode::Whole),
+            "both" => Ok(CodeMode::Both),
+            _ => Err(format!(
+                "unknown code mode '{s}' (expected split, whole or both)"
+            )),
+        }
+    }
+}
+
+const RUST: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "type",
+    "unsafe", "use", "where", "while", "yield",
+];
+
+const PYTHON: &[&str] = &[
+    "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class", "continue",
+    "def", "del", "elif", "else", "except", "finally", "for", "from", "global", "if", "import",
+    "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return", "try", "while",
+    "with", "yield", "self", "cls",
+];
+
+const JAVASCRIPT: &[&str] = &[
+    "break",
+    "case",
+    "catch",
+    "class",
+    "const",
+    "continue",
+    "debugger",
+    "default",
+    "delete",
+    "do",
+    "else",
+    "export",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "if",
+    "import",
+    "in",
+    "instanceof",
+    "let",
+    "new",
+    "null",
+    "return",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "undefined",
+    "var",
+    "void",
+    "while",
+    "with",
+    "yield",
+    "async",
+    "await",
+    "of",
+    "static",
+    "interface",
+    "implements",
+    "private",
+    "protected",
+    "public",
+    "readonly",
+    "type",
+    "enum",
+    "namespace",
+    "declare",
+    "abstract",
+];
+
+const GO: &[&str] = &[
+    "break",
+    "case",
+    "chan",
+    "const",
+    "continue",
+    "default",
+    "defer",
+    "else",
+    "fallthrough",
+    "for",
+    "func",
+    "go",
+    "goto",
+    "if",
+    "import",
+    "interface",
+    "map",
+    "package",
+    "range",
+    "return",
+    "select",
+    "struct",
+    "switch",
+    "type",
+    "var",
+    "nil",
+];
+
+const C_FAMILY: &[&str] = &[
+    "auto",
+    "bool",
+    "break",
+    "case",
+    "char",
+    "const",
+    "continue",
+    "default",
+    "do",
+    "double",
+    "else",
+    "enum",
+    "extern",
+    "float",
+    "for",
+    "goto",
+    "if",
+    "include",
+    "define",
+    "ifdef",
+    "ifndef",
+    "endif",
+    "inline",
+    "int",
+    "long",
+    "register",
+    "return",
+    "short",
+    "signed",
+    "sizeof",
+    "static",
+    "struct",
+    "switch",
+    "typedef",
+    "union",
+    "unsigned",
+    "void",
+    "volatile",
+    "while",
+    "class",
+    "namespace",
+    "template",
+    "typename",
+    "public",
+    "private",
+    "protected",
+    "virtual",
+    "override",
+    "new",
+    "delete",
+    "this",
+    "nullptr",
+    "true",
+    "false",
+    "using",
+    "final",
+    "extends",
+    "implements",
+    "import",
+    "package",
+    "throws",
+    "throw",
+    "try",
+    "catch",
+    "finally",
+    "boolean",
+    "byte",
+    "null",
+    "super",
+    "synchronized",
+    "instanceof",
+    "abstract",
+];
+
+/// Langage d'un fichier source, pour ses mots-clés (`--code-lang`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeLang {
+    Rust,
+    Python,
+    /// JavaScript et TypeScript
+    JavaScript,
+    Go,
+    /// C, C++ et Java
+    CFamily,
+}
+
+impl CodeLang {
+    /// D'après l'extension du fichier ("main.rs", "app.tsx"...)
+    pub fn from_path(path: &str) -> Option<Self> {
+        let ext = Path::new(path).extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "rs" => Some(CodeLang::Rust),
+            "py" | "pyi" => Some(CodeLang::Python),
+            "js" | "mjs" | "cjs" | "jsx" | "ts" | "tsx" => Some(CodeLang::JavaScript),
+            "go" => Some(CodeLang::Go),
+            "c" | "h" | "cc" | "cpp" | "cxx" | "hpp" | "hh" | "java" => Some(CodeLang::CFamily),
+            _ => None,
+        }
+    }
+
+    pub fn keywords(self) -> &'static [&'static str] {
+        match self {
+            CodeLang::Rust => RUST,
+            CodeLang::Python => PYTHON,
+            CodeLang::JavaScript => JAVASCRIPT,
+            CodeLang::Go => GO,
+            CodeLang::CFamily => C_FAMILY,
+        }
+    }
+
+    /// Mot-clé du langage (sensible à la casse : `None` en Python, mais
+    /// pas `none`)
+    pub fn is_keyword(self, ident: &str) -> bool {
+        self.keywords().contains(&ident)
+    }
+}
+
+impl FromStr for CodeLang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rust" | "rs" => Ok(CodeLang::Rust),
+            "python" | "py" => Ok(CodeLang::Python),
+            "javascript" | "js" | "typescript" | "ts" => Ok(CodeLang::JavaScript),
+            "go" => Ok(CodeLang::Go),
+            "c" | "c++" | "cpp" | "java" => Ok(CodeLang::CFamily),
+            _ => Err(format!(
+                "unknown language '{s}' (expected rust, python, javascript, go, c, c++ or java)"
+            )),
+        }
+    }
+}
+
+/// Plages des sous-mots d'un identifiant. Les `_` séparent, une majuscule
+/// après une minuscule ou un chiffre commence un mot, et un sigle s'arrête
+/// avant la majuscule qui commence le mot suivant (`HTTPServer` → HTTP,
+/// Server). Les chiffres restent collés au mot qui les précède (`utf8`).
+pub fn subwords(ident: &str) -> Vec<Range<usize>> {
+    let chars: Vec<(usize, char)> = ident.char_indices().collect();
+    let mut out = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for (k, &(i, c)) in chars.iter().enumerate() {
+        if c == '_' {
+            if let Some(s) = start.take() {
+                out.push(s..i);
+            }
+            continue;
+        }
+
+        if let Some(s) = start
+            && k > 0
+        {
+            let prev = chars[k - 1].1;
+            let next = chars.get(k + 1).map(|&(_, n)| n);
+            let camel = c.is_uppercase() && (prev.is_lowercase() || prev.is_numeric());
+            let acronym_end =
+                c.is_uppercase() && prev.is_uppercase() && next.is_some_and(char::is_lowercase);
+            if camel || acronym_end {
+                out.push(s..i);
+                start = Some(i);
+            }
+        }
+        start.get_or_insert(i);
+    }
+
+    if let Some(s) = start {
+        out.push(s..ident.len());
+    }
+    out
+}
+
+/// Les plages à compter pour un identifiant selon `mode` (relatives à
+/// l'identifiant). Un identifiant d'un seul mot n'est compté qu'une fois.
+pub fn parts(ident: &str, mode: CodeMode) -> Vec<Range<usize>> {
+    let whole = 0..ident.len();
+    match mode {
+        CodeMode::Whole => vec![whole],
+        CodeMode::Split => subwords(ident),
+        CodeMode::Both => {
+            let subs = subwords(ident);
+            if subs.len() == 1 && subs[0] == whole {
+                subs
+            } else {
+                std::iter::once(whole).chain(subs).collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(ident: &str) -> Vec<&str> {
+        subwords(ident).into_iter().map(|r| &ident[r]).collect()
+    }
+
+    #[test]
+    fn splits_common_conventions() {
+        assert_eq!(split("parseHttpRequest"), vec!["parse", "Http", "Request"]);
+        assert_eq!(split("WordCounter"), vec!["Word", "Counter"]);
+        assert_eq!(split("min_length"), vec!["min", "length"]);
+        assert_eq!(split("MAX_BUFFER_SIZE"), vec!["MAX", "BUFFER", "SIZE"]);
+        assert_eq!(split("HTTPServer"), vec!["HTTP", "Server"]);
+        assert_eq!(split("utf8Decoder"), vec!["utf8", "Decoder"]);
+        assert_eq!(split("__init__"), vec!["init"]);
+        assert_eq!(split("x"), vec!["x"]);
+    }
+
+    #[test]
+    fn both_mode_counts_single_words_once() {
+        assert_eq!(parts("getName", CodeMode::Both), vec![0..7, 0..3, 3..7]);
+        assert_eq!(parts("name", CodeMode::Both), vec![0..4]);
+        assert_eq!(parts("get_name", CodeMode::Whole), vec![0..8]);
+    }
+
+    #[test]
+    fn keywords_depend_on_the_language() {
+        assert!(CodeLang::Rust.is_keyword("fn"));
+        assert!(CodeLang::Python.is_keyword("None"));
+        assert!(!CodeLang::Python.is_keyword("none"));
+        assert!(CodeLang::JavaScript.is_keyword("function"));
+        // mots-clés ailleurs, identifiants ordinaires en Rust
+        for ident in [
+            "map", "range", "select", "package", "new", "delete", "this", "default",
+        ] {
+            assert!(!CodeLang::Rust.is_keyword(ident), "{ident}");
+        }
+        assert!(CodeLang::Go.is_keyword("map"));
+    }
+
+    #[test]
+    fn language_from_extension() {
+        assert_eq!(CodeLang::from_path("src/main.rs"), Some(CodeLang::Rust));
+        assert_eq!(CodeLang::from_path("App.TSX"), Some(CodeLang::JavaScript));
+        assert_eq!(CodeLang::from_path("Main.java"), Some(CodeLang::CFamily));
+        assert_eq!(CodeLang::from_path("notes.txt"), None);
+        assert_eq!(CodeLang::from_path("<stdin>"), None);
+        assert_eq!("c++".parse(), Ok(CodeLang::CFamily));
+    }
+}
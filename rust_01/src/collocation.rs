@@ -0,0 +1,188 @@
+//! Détection de collocations (`--collocations`) : paires de mots qui
+//! apparaissent ensemble dans une fenêtre plus souvent que le hasard.
+
+use crate::tokenizer::Tokenizer;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Mesure d'association utilisée pour classer les paires
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Measure {
+    /// Pointwise mutual information : log2(O / E)
+    #[default]
+    Pmi,
+    /// (O - E) / sqrt(O)
+    TScore,
+    /// G² de Dunning sur la table de contingence 2x2
+    LogLikelihood,
+}
+
+impl FromStr for Measure {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pmi" => Ok(Measure::Pmi),
+            "t-score" | "t" => Ok(Measure::TScore),
+            "llr" | "log-likelihood" => Ok(Measure::LogLikelihood),
+            _ => Err(format!(
+                "unknown measure '{s}' (expected pmi, t-score or llr)"
+            )),
+        }
+    }
+}
+
+impl Measure {
+    pub fn name(self) -> &'static str {
+        match self {
+            Measure::Pmi => "pmi",
+            Measure::TScore => "t-score",
+            Measure::LogLikelihood => "log-likelihood",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Collocation {
+    pub first: String,
+    pub second: String,
+    pub count: usize,
+    pub score: f64,
+}
+
+/// Compte les paires (a, b) où b suit a à moins de `window - 1` mots.
+/// Une fenêtre de 2 correspond aux bigrammes adjacents.
+#[derive(Debug, Clone)]
+pub struct CollocationFinder {
+    window: usize,
+    pairs: HashMap<(String, String), usize>,
+    /// nombre de paires où le mot est en première / seconde position
+    as_first: HashMap<String, usize>,
+    as_second: HashMap<String, usize>,
+    total_pairs: usize,
+}
+
+impl CollocationFinder {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(2),
+            pairs: HashMap::new(),
+            as_first: HashMap::new(),
+            as_second: HashMap::new(),
+            total_pairs: 0,
+        }
+    }
+
+    /// Ajoute les paires d'un texte ; les paires ne traversent pas les textes
+    pub fn feed(&mut self, tokenizer: &Tokenizer, text: &str) {
+        let words: Vec<String> = tokenizer.words(text).map(|w| w.into_owned()).collect();
+
+        for (i, a) in words.iter().enumerate() {
+            for b in words.iter().skip(i + 1).take(self.window - 1) {
+                *self.pairs.entry((a.clone(), b.clone())).or_insert(0) += 1;
+                *self.as_first.entry(a.clone()).or_insert(0) += 1;
+                *self.as_second.entry(b.clone()).or_insert(0) += 1;
+                self.total_pairs += 1;
+            }
+        }
+    }
+
+    /// Paires vues au moins `min_freq` fois, de la plus associée à la moins
+    pub fn ranked(&self, measure: Measure, min_freq: usize) -> Vec<Collocation> {
+        let n = self.total_pairs as f64;
+        let mut out: Vec<Collocation> = self
+            .pairs
+            .iter()
+            .filter(|(_, c)| **c >= min_freq.max(1))
+            .map(|((a, b), &count)| {
+                let fa = self.as_first[a] as f64;
+                let fb = self.as_second[b] as f64;
+                Collocation {
+                    first: a.clone(),
+                    second: b.clone(),
+                    count,
+                    score: score(measure, count as f64, fa, fb, n),
+                }
+            })
+            .collect();
+
+        out.sort_by(|x, y| {
+            y.score
+                .total_cmp(&x.score)
+                .then_with(|| y.count.cmp(&x.count))
+                .then_with(|| (&x.first, &x.second).cmp(&(&y.first, &y.second)))
+        });
+        out
+    }
+}
+
+/// `o` : occurrences de la paire, `fa` / `fb` : marges, `n` : nombre de paires
+fn score(measure: Measure, o: f64, fa: f64, fb: f64, n: f64) -> f64 {
+    let expected = fa * fb / n;
+    match measure {
+        Measure::Pmi => (o / expected).log2(),
+        Measure::TScore => (o - expected) / o.sqrt(),
+        Measure::LogLikelihood => {
+            // table de contingence : (a, b), (a, ¬b), (¬a, b), (¬a, ¬b)
+            let observed = [o, fa - o, fb - o, n - fa - fb + o];
+            let expected = [
+                fa * fb / n,
+                fa * (n - fb) / n,
+                (n - fa) * fb / n,
+                (n - fa) * (n - fb) / n,
+            ];
+            2.0 * observed
+                .iter()
+                .zip(expected)
+                .filter(|(k, _)| **k > 0.0)
+                .map(|(k, e)| k * (k / e).ln())
+                .sum::<f64>()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn finder(text: &str, window: usize) -> CollocationFinder {
+        let mut f = CollocationFinder::new(window);
+        f.feed(&Tokenizer::new().ignore_case(true), text);
+        f
+    }
+
+    #[test]
+    fn strong_pair_ranks_first() {
+        let text = "the new Product Manager said the product manager was right \
+                    while the team and the product manager agreed the plan";
+        let f = finder(text, 2);
+
+        for m in [Measure::Pmi, Measure::TScore, Measure::LogLikelihood] {
+            let top = &f.ranked(m, 3)[0];
+            assert_eq!(
+                (top.first.as_str(), top.second.as_str()),
+                ("product", "manager")
+            );
+            assert_eq!(top.count, 3);
+        }
+    }
+
+    #[test]
+    fn window_counts_pairs_at_distance() {
+        let f = finder("a x b a y b", 3);
+        let ranked = f.ranked(Measure::Pmi, 2);
+
+        assert_eq!(ranked.len(), 1);
+        assert_eq!(
+            (ranked[0].first.as_str(), ranked[0].second.as_str()),
+            ("a", "b")
+        );
+    }
+
+    #[test]
+    fn min_freq_filters_rare_pairs() {
+        let f = finder("one two three", 2);
+        assert!(f.ranked(Measure::Pmi, 2).is_empty());
+        assert_eq!(f.ranked(Measure::Pmi, 1).len(), 2);
+    }
+}
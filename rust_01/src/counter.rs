@@ -0,0 +1,324 @@
+use crate::intern::{Interner, Symbol};
+use crate::locale::Collator;
+use crate::tokenizer::{Tokenizer, Unit, byte_key};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// Compteur de fréquences de mots, alimentable au fil de l'eau.
+///
+/// Chaque appel à [`WordCounter::feed`] découpe son morceau de texte
+/// indépendamment : un mot (ou une citation) coupé entre deux morceaux
+/// est compté comme deux tokens.
+///
+/// Les mots sont internés (voir [`crate::intern`]) : `counts[sym]` est la
+/// fréquence du mot `sym`, et un mot déjà vu est compté sans allocation.
+#[derive(Debug, Clone, Default)]
+pub struct WordCounter {
+    tokenizer: Tokenizer,
+    words: Interner,
+    counts: Vec<usize>,
+    total: usize,
+}
+
+impl WordCounter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_tokenizer(tokenizer: Tokenizer) -> Self {
+        Self {
+            tokenizer,
+            ..Self::default()
+        }
+    }
+
+    pub fn tokenizer(&self) -> &Tokenizer {
+        &self.tokenizer
+    }
+
+    /// Découpe `text` et ajoute ses mots aux compteurs
+    pub fn feed(&mut self, text: &str) {
+        if self.tokenizer.get_unit() == Unit::Byte {
+            self.feed_bytes(text.as_bytes());
+            return;
+        }
+
+        let Self {
+            tokenizer,
+            words,
+            counts,
+            total,
+        } = self;
+        count_words(words, counts, total, tokenizer, text);
+    }
+
+    /// Comme [`WordCounter::feed`] mais avec un autre `Tokenizer`, par exemple
+    /// pour appliquer une liste de mots-outils propre à chaque document ;
+    /// en `--unit byte`, ce sont les octets bruts de `text` qui sont comptés
+    pub fn feed_with(&mut self, tokenizer: &Tokenizer, text: &str) {
+        if tokenizer.get_unit() == Unit::Byte {
+            self.count_bytes(text.as_bytes(), tokenizer.is_ignore_case());
+            return;
+        }
+
+        count_words(
+            &mut self.words,
+            &mut self.counts,
+            &mut self.total,
+            tokenizer,
+            text,
+        );
+    }
+
+    /// Compte les octets bruts de `bytes` (`--unit byte`), sans décodage
+    pub fn feed_bytes(&mut self, bytes: &[u8]) {
+        self.count_bytes(bytes, self.tokenizer.is_ignore_case());
+    }
+
+    fn count_bytes(&mut self, bytes: &[u8], ignore_case: bool) {
+        let mut per_byte = [0usize; 256];
+        for &b in bytes {
+            let b = if ignore_case {
+                b.to_ascii_lowercase()
+            } else {
+                b
+            };
+            per_byte[b as usize] += 1;
+        }
+
+        for (b, &n) in per_byte.iter().enumerate() {
+            self.add(&byte_key(b as u8), n);
+        }
+    }
+
+    /// Ajoute `n` occurrences d'un mot déjà normalisé
+    pub fn add(&mut self, word: &str, n: usize) {
+        if n == 0 {
+            return;
+        }
+        bump(&mut self.words, &mut self.counts, word, n);
+        self.total += n;
+    }
+
+    /// Additionne les compteurs d'un autre `WordCounter`
+    pub fn merge(&mut self, other: WordCounter) {
+        for (w, c) in &other {
+            self.add(w, c);
+        }
+    }
+
+    pub fn get(&self, word: &str) -> usize {
+        self.words
+            .get(word)
+            .map_or(0, |sym| self.counts[sym as usize])
+    }
+
+    /// Nombre total de mots comptés
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// Nombre de mots distincts
+    pub fn unique(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Itère sur (mot, fréquence), sans ordre particulier
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            counter: self,
+            next: 0,
+        }
+    }
+
+    /// Tous les mots, triés par fréquence décroissante puis selon `collator`
+    pub fn ranked(&self, collator: &Collator) -> Vec<(&str, usize)> {
+        let mut items: Vec<(&str, usize)> = self.iter().collect();
+        // une clé de collation par mot, pas deux par comparaison
+        items.sort_by_cached_key(|&(w, c)| (Reverse(c), collator.sort_key(w), w));
+        items
+    }
+
+    /// Les `n` mots les plus fréquents
+    pub fn top(&self, n: usize) -> Vec<(&str, usize)> {
+        let mut items = self.ranked(&Collator::default());
+        items.truncate(n);
+        items
+    }
+}
+
+fn bump(words: &mut Interner, counts: &mut Vec<usize>, word: &str, n: usize) {
+    let sym = words.intern(word) as usize;
+    if sym == counts.len() {
+        counts.push(0);
+    }
+    counts[sym] += n;
+}
+
+fn count_words(
+    words: &mut Interner,
+    counts: &mut Vec<usize>,
+    total: &mut usize,
+    tokenizer: &Tokenizer,
+    text: &str,
+) {
+    for w in tokenizer.words(text) {
+        // le mot emprunté sert de clé : pas d'allocation s'il est déjà connu
+        bump(words, counts, &w, 1);
+        *total += 1;
+    }
+}
+
+pub struct Iter<'a> {
+    counter: &'a WordCounter,
+    next: usize,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a str, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let c = *self.counter.counts.get(self.next)?;
+        let w = self.counter.words.resolve(self.next as Symbol);
+        self.next += 1;
+        Some((w, c))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.counter.counts.len() - self.next;
+        (n, Some(n))
+    }
+}
+
+impl<'a> IntoIterator for &'a WordCounter {
+    type Item = (&'a str, usize);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Même forme JSON qu'avant l'internement : `{"counts": {mot: n}, "total": n}`
+impl Serialize for WordCounter {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct Counts<'a>(&'a WordCounter);
+
+        impl Serialize for Counts<'_> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut map = serializer.serialize_map(Some(self.0.unique()))?;
+                for (w, c) in self.0 {
+                    map.serialize_entry(w, &c)?;
+                }
+                map.end()
+            }
+        }
+
+        #[derive(Serialize)]
+        struct Raw<'a> {
+            counts: Counts<'a>,
+            total: usize,
+        }
+
+        Raw {
+            counts: Counts(self),
+            total: self.total,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for WordCounter {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Raw {
+            counts: HashMap<String, usize>,
+            total: usize,
+        }
+
+        let raw = Raw::deserialize(deserializer)?;
+        let mut counter = WordCounter::new();
+        for (w, c) in &raw.counts {
+            counter.add(w, *c);
+        }
+        counter.total = raw.total;
+        Ok(counter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn feed_accumulates_across_chunks() {
+        let mut c = WordCounter::new();
+        c.feed("the cat");
+        c.feed("the dog the end");
+
+        assert_eq!(c.get("the"), 3);
+        assert_eq!(c.get("cat"), 1);
+        assert_eq!(c.total(), 6);
+        assert_eq!(c.unique(), 4);
+    }
+
+    #[test]
+    fn tokenizer_options_apply_to_feed() {
+        let mut c =
+            WordCounter::with_tokenizer(Tokenizer::new().ignore_case(true).min_length(Some(3)));
+        c.feed("The THE the a an cat");
+
+        assert_eq!(c.get("the"), 3);
+        assert_eq!(c.get("a"), 0);
+        assert_eq!(c.total(), 4);
+    }
+
+    #[test]
+    fn byte_unit_counts_raw_bytes() {
+        let mut c = WordCounter::with_tokenizer(Tokenizer::new().unit(Unit::Byte));
+        c.feed_bytes(b"AAb\xc3");
+
+        assert_eq!(c.get("0x41 'A'"), 2);
+        assert_eq!(c.get("0xc3"), 1);
+        assert_eq!(c.total(), 4);
+
+        // feed_with suit l'unité du tokenizer qu'on lui passe
+        let mut c = WordCounter::new();
+        c.feed_with(&Tokenizer::new().unit(Unit::Byte), "éé a");
+        assert_eq!(c.get("0xc3"), 2);
+        assert_eq!(c.get("0xa9"), 2);
+        assert_eq!(c.get("0x20"), 1);
+        assert_eq!(c.get("0x61 'a'"), 1);
+        assert_eq!(c.total(), 6);
+    }
+
+    #[test]
+    fn merge_and_top() {
+        let mut a = WordCounter::new();
+        a.feed("b a a");
+        let mut b = WordCounter::new();
+        b.feed("b c");
+
+        a.merge(b);
+        assert_eq!(a.top(2), vec![("a", 2), ("b", 2)]);
+        assert_eq!(a.total(), 5);
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let mut c = WordCounter::new();
+        c.feed("x y y");
+
+        let json = serde_json::to_string(&c).unwrap();
+        let back: WordCounter = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(back.get("y"), 2);
+        assert_eq!(back.total(), 3);
+    }
+}
@@ -0,0 +1,309 @@
+//! Décodage des octets d'entrée en texte (`--encoding`, `--lossy`).
+
+use std::fmt;
+use std::str::FromStr;
+
+/// Encodage demandé pour l'entrée
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// BOM UTF-8 / UTF-16 si présent, sinon UTF-8
+    #[default]
+    Auto,
+    Utf8,
+    Latin1,
+    Windows1252,
+    /// BOM si présent, sinon little-endian
+    Utf16,
+    Utf16Le,
+    Utf16Be,
+}
+
+impl Encoding {
+    pub fn name(self) -> &'static str {
+        match self {
+            Encoding::Auto => "auto",
+            Encoding::Utf8 => "utf-8",
+            Encoding::Latin1 => "latin1",
+            Encoding::Windows1252 => "windows-1252",
+            Encoding::Utf16 => "utf-16",
+            Encoding::Utf16Le => "utf-16le",
+            Encoding::Utf16Be => "utf-16be",
+        }
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "auto" => Ok(Encoding::Auto),
+            "utf-8" | "utf8" => Ok(Encoding::Utf8),
+            "latin1" | "latin-1" | "iso-8859-1" => Ok(Encoding::Latin1),
+            "windows-1252" | "cp1252" => Ok(Encoding::Windows1252),
+            "utf-16" | "utf16" => Ok(Encoding::Utf16),
+            "utf-16le" => Ok(Encoding::Utf16Le),
+            "utf-16be" => Ok(Encoding::Utf16Be),
+            _ => Err(format!(
+                "unknown encoding '{s}' (expected auto, utf-8, latin1, windows-1252, utf-16, utf-16le or utf-16be)"
+            )),
+        }
+    }
+}
+
+/// Texte décodé + nombre d'octets remplacés par U+FFFD (mode lossy)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Decoded {
+    pub text: String,
+    pub encoding: Encoding,
+    pub replaced_bytes: usize,
+}
+
+/// Séquence invalide rencontrée en mode strict
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    pub encoding: Encoding,
+    pub offset: usize,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "input is not valid {} (invalid byte at offset {}); try --encoding or --lossy",
+            self.encoding.name(),
+            self.offset
+        )
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Windows-1252 : 0x80..=0x9F (les autres octets sont identiques à Latin-1).
+/// `None` = octet non défini.
+const CP1252_HIGH: [Option<char>; 32] = [
+    Some('\u{20AC}'),
+    None,
+    Some('\u{201A}'),
+    Some('\u{0192}'),
+    Some('\u{201E}'),
+    Some('\u{2026}'),
+    Some('\u{2020}'),
+    Some('\u{2021}'),
+    Some('\u{02C6}'),
+    Some('\u{2030}'),
+    Some('\u{0160}'),
+    Some('\u{2039}'),
+    Some('\u{0152}'),
+    None,
+    Some('\u{017D}'),
+    None,
+    None,
+    Some('\u{2018}'),
+    Some('\u{2019}'),
+    Some('\u{201C}'),
+    Some('\u{201D}'),
+    Some('\u{2022}'),
+    Some('\u{2013}'),
+    Some('\u{2014}'),
+    Some('\u{02DC}'),
+    Some('\u{2122}'),
+    Some('\u{0161}'),
+    Some('\u{203A}'),
+    Some('\u{0153}'),
+    None,
+    Some('\u{017E}'),
+    Some('\u{0178}'),
+];
+
+/// Décode `bytes` selon `encoding`. En mode `lossy`, les séquences invalides
+/// sont remplacées par U+FFFD au lieu de renvoyer une erreur.
+pub fn decode(bytes: &[u8], encoding: Encoding, lossy: bool) -> Result<Decoded, DecodeError> {
+    let (encoding, body) = match encoding {
+        Encoding::Auto => detect_bom(bytes),
+        Encoding::Utf8 => (Encoding::Utf8, strip(bytes, &[0xEF, 0xBB, 0xBF])),
+        Encoding::Utf16 => match detect_bom(bytes) {
+            (e @ (Encoding::Utf16Le | Encoding::Utf16Be), body) => (e, body),
+            _ => (Encoding::Utf16Le, bytes),
+        },
+        Encoding::Utf16Le => (Encoding::Utf16Le, strip(bytes, &[0xFF, 0xFE])),
+        Encoding::Utf16Be => (Encoding::Utf16Be, strip(bytes, &[0xFE, 0xFF])),
+        other => (other, bytes),
+    };
+    // offset des erreurs dans le fichier d'origine (BOM compris)
+    let skipped = bytes.len() - body.len();
+
+    let decoded = match encoding {
+        Encoding::Latin1 => Ok((body.iter().map(|&b| b as char).collect(), 0)),
+        Encoding::Windows1252 => decode_cp1252(body, lossy),
+        Encoding::Utf16Le | Encoding::Utf16Be => decode_utf16(body, encoding, lossy),
+        _ => decode_utf8(body, lossy),
+    };
+    let (text, replaced_bytes) = decoded.map_err(|e| DecodeError {
+        offset: e.offset + skipped,
+        ..e
+    })?;
+
+    Ok(Decoded {
+        text,
+        encoding,
+        replaced_bytes,
+    })
+}
+
+fn detect_bom(bytes: &[u8]) -> (Encoding, &[u8]) {
+    match bytes {
+        [0xEF, 0xBB, 0xBF, rest @ ..] => (Encoding::Utf8, rest),
+        [0xFF, 0xFE, rest @ ..] => (Encoding::Utf16Le, rest),
+        [0xFE, 0xFF, rest @ ..] => (Encoding::Utf16Be, rest),
+        _ => (Encoding::Utf8, bytes),
+    }
+}
+
+fn strip<'a>(bytes: &'a [u8], bom: &[u8]) -> &'a [u8] {
+    bytes.strip_prefix(bom).unwrap_or(bytes)
+}
+
+fn decode_utf8(bytes: &[u8], lossy: bool) -> Result<(String, usize), DecodeError> {
+    let mut text = String::with_capacity(bytes.len());
+    let mut replaced = 0;
+    let mut offset = 0;
+
+    for chunk in bytes.utf8_chunks() {
+        text.push_str(chunk.valid());
+        offset += chunk.valid().len();
+
+        let invalid = chunk.invalid();
+        if !invalid.is_empty() {
+            if !lossy {
+                return Err(DecodeError {
+                    encoding: Encoding::Utf8,
+                    offset,
+                });
+            }
+            text.push(char::REPLACEMENT_CHARACTER);
+            replaced += invalid.len();
+            offset += invalid.len();
+        }
+    }
+
+    Ok((text, replaced))
+}
+
+fn decode_cp1252(bytes: &[u8], lossy: bool) -> Result<(String, usize), DecodeError> {
+    let mut text = String::with_capacity(bytes.len());
+    let mut replaced = 0;
+
+    for (i, &b) in bytes.iter().enumerate() {
+        let c = match b {
+            0x80..=0x9F => CP1252_HIGH[(b - 0x80) as usize],
+            _ => Some(b as char),
+        };
+        match c {
+            Some(c) => text.push(c),
+            None if lossy => {
+                text.push(char::REPLACEMENT_CHARACTER);
+                replaced += 1;
+            }
+            None => {
+                return Err(DecodeError {
+                    encoding: Encoding::Windows1252,
+                    offset: i,
+                });
+            }
+        }
+    }
+
+    Ok((text, replaced))
+}
+
+fn decode_utf16(
+    bytes: &[u8],
+    encoding: Encoding,
+    lossy: bool,
+) -> Result<(String, usize), DecodeError> {
+    let units = bytes.chunks_exact(2).map(|p| match encoding {
+        Encoding::Utf16Be => u16::from_be_bytes([p[0], p[1]]),
+        _ => u16::from_le_bytes([p[0], p[1]]),
+    });
+
+    let mut text = String::with_capacity(bytes.len() / 2);
+    let mut replaced = 0;
+    let mut offset = 0;
+
+    for r in char::decode_utf16(units) {
+        match r {
+            Ok(c) => {
+                text.push(c);
+                offset += c.len_utf16() * 2;
+            }
+            Err(_) if lossy => {
+                text.push(char::REPLACEMENT_CHARACTER);
+                replaced += 2;
+                offset += 2;
+            }
+            Err(_) => return Err(DecodeError { encoding, offset }),
+        }
+    }
+
+    // octet isolé en fin de fichier
+    if bytes.len() % 2 == 1 {
+        if !lossy {
+            return Err(DecodeError { encoding, offset });
+        }
+        text.push(char::REPLACEMENT_CHARACTER);
+        replaced += 1;
+    }
+
+    Ok((text, replaced))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_utf16_boms() {
+        let le = [0xFF, 0xFE, b'h', 0, b'i', 0];
+        let d = decode(&le, Encoding::Auto, false).unwrap();
+        assert_eq!(d.text, "hi");
+        assert_eq!(d.encoding, Encoding::Utf16Le);
+
+        let be = [0xFE, 0xFF, 0, b'h', 0, b'i'];
+        assert_eq!(decode(&be, Encoding::Auto, false).unwrap().text, "hi");
+    }
+
+    #[test]
+    fn invalid_utf8_is_an_error_unless_lossy() {
+        let bytes = b"caf\xe9 ok";
+        let err = decode(bytes, Encoding::Auto, false).unwrap_err();
+        assert_eq!(err.offset, 3);
+
+        let d = decode(bytes, Encoding::Auto, true).unwrap();
+        assert_eq!(d.text, "caf\u{FFFD} ok");
+        assert_eq!(d.replaced_bytes, 1);
+    }
+
+    #[test]
+    fn single_byte_encodings() {
+        assert_eq!(
+            decode(b"caf\xe9", Encoding::Latin1, false).unwrap().text,
+            "café"
+        );
+        assert_eq!(
+            decode(b"\x93hi\x94 \x80", Encoding::Windows1252, false)
+                .unwrap()
+                .text,
+            "\u{201C}hi\u{201D} €"
+        );
+        assert!(decode(b"\x81", Encoding::Windows1252, false).is_err());
+    }
+
+    #[test]
+    fn error_offset_accounts_for_bom() {
+        // BOM UTF-16LE puis un surrogate isolé
+        let bytes = [0xFF, 0xFE, b'a', 0, 0x00, 0xD8, b'b', 0];
+        let err = decode(&bytes, Encoding::Auto, false).unwrap_err();
+        assert_eq!(err.offset, 4);
+    }
+}
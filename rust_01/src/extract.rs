@@ -0,0 +1,517 @@
+//! Extraction du texte "lisible" avant le comptage (`--input-format`).
+//!
+//! Le contenu retiré est remplacé par des espaces et les retours à la ligne
+//! sont conservés, pour que les numéros de ligne restent ceux du fichier
+//! d'origine.
+
+use std::borrow::Cow;
+use std::str::FromStr;
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InputFormat {
+    #[default]
+    Text,
+    Html,
+    Markdown,
+    /// Seulement les commentaires et les chaînes littérales du code source
+    CodeComments,
+}
+
+impl FromStr for InputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "text" => Ok(InputFormat::Text),
+            "html" => Ok(InputFormat::Html),
+            "markdown" | "md" => Ok(InputFormat::Markdown),
+            "code-comments" => Ok(InputFormat::CodeComments),
+            _ => Err(format!(
+                "unknown input format '{s}' (expected text, html, markdown or code-comments)"
+            )),
+        }
+    }
+}
+
+/// Renvoie le texte à compter selon le format d'entrée
+pub fn extract(input: &str, format: InputFormat) -> String {
+    match format {
+        InputFormat::Text => input.to_string(),
+        InputFormat::Html => extract_html(input),
+        InputFormat::Markdown => extract_markdown(input),
+        InputFormat::CodeComments => extract_code_comments(input),
+    }
+}
+
+/// Remplace un morceau retiré par du blanc, en gardant ses retours à la ligne
+fn blank(out: &mut String, removed: &str) {
+    for c in removed.chars() {
+        out.push(if c == '\n' { '\n' } else { ' ' });
+    }
+}
+
+// ---------------------------------------------------------------- HTML
+
+/// Balises dont le contenu n'est pas du texte
+const RAW_TEXT_TAGS: &[&str] = &["script", "style", "template", "noscript"];
+
+pub fn extract_html(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(lt) = rest.find('<') {
+        push_decoded(&mut out, &rest[..lt]);
+        rest = &rest[lt..];
+
+        // commentaire <!-- ... -->
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map_or(rest.len(), |i| i + 3);
+            blank(&mut out, &rest[..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        let Some(gt) = tag_end(rest) else {
+            // '<' isolé : on le garde comme texte
+            out.push('<');
+            rest = &rest[1..];
+            continue;
+        };
+
+        let tag = &rest[..gt];
+        blank(&mut out, tag);
+        rest = &rest[gt..];
+
+        let name = tag_name(tag);
+        if !tag.starts_with("</") && RAW_TEXT_TAGS.contains(&name.as_str()) {
+            // saute tout jusqu'à la balise fermante
+            let close = format!("</{name}");
+            let end = find_ascii_ci(rest, &close).unwrap_or(rest.len());
+            blank(&mut out, &rest[..end]);
+            rest = &rest[end..];
+        }
+    }
+
+    push_decoded(&mut out, rest);
+    out
+}
+
+/// Position juste après le '>' qui ferme la balise (en ignorant ceux dans
+/// les valeurs d'attributs entre guillemets)
+fn tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in s.char_indices().skip(1) {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return Some(i + 1),
+            // '<' suivi d'autre chose qu'une lettre, '/' ou '!' : pas une balise
+            (None, _) if i == 1 && !(c.is_ascii_alphabetic() || c == '/' || c == '!') => {
+                return None;
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+fn tag_name(tag: &str) -> String {
+    tag.trim_start_matches('<')
+        .trim_start_matches('/')
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_ascii_lowercase()
+}
+
+fn find_ascii_ci(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|w| w.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+/// Ajoute `text` en décodant les entités (&amp; &eacute; &#233; &#xE9;...)
+fn push_decoded(out: &mut String, text: &str) {
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+
+        let decoded = rest
+            .find(';')
+            .filter(|&semi| semi <= 32)
+            .and_then(|semi| decode_entity(&rest[1..semi]).map(|c| (c, semi + 1)));
+
+        match decoded {
+            Some((c, len)) => {
+                out.push(c);
+                rest = &rest[len..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+}
+
+fn decode_entity(name: &str) -> Option<char> {
+    if let Some(num) = name.strip_prefix('#') {
+        let code = match num.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => num.parse().ok()?,
+        };
+        return char::from_u32(code);
+    }
+
+    let c = match name {
+        "amp" => '&',
+        "lt" => '<',
+        "gt" => '>',
+        "quot" => '"',
+        "apos" => '\'',
+        "nbsp" => ' ',
+        "copy" => '©',
+        "reg" => '®',
+        "hellip" => '…',
+        "mdash" => '—',
+        "ndash" => '–',
+        "lsquo" => '‘',
+        "rsquo" => '’',
+        "ldquo" => '“',
+        "rdquo" => '”',
+        "laquo" => '«',
+        "raquo" => '»',
+        "euro" => '€',
+        _ => return decode_latin1_entity(name),
+    };
+    Some(c)
+}
+
+/// Entités des lettres accentuées : &eacute; &Agrave; &ccedil; &ouml;...
+fn decode_latin1_entity(name: &str) -> Option<char> {
+    const ACCENTS: &[(&str, char)] = &[
+        ("acute", '\u{301}'),
+        ("grave", '\u{300}'),
+        ("circ", '\u{302}'),
+        ("uml", '\u{308}'),
+        ("tilde", '\u{303}'),
+        ("cedil", '\u{327}'),
+        ("ring", '\u{30A}'),
+    ];
+
+    let mut chars = name.chars();
+    let base = chars.next().filter(|c| c.is_ascii_alphabetic())?;
+    let accent = chars.as_str();
+    let (_, mark) = ACCENTS.iter().find(|(n, _)| *n == accent)?;
+
+    let composed: String = [base, *mark].into_iter().nfc().collect();
+    let mut it = composed.chars();
+    match (it.next(), it.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+// ------------------------------------------------------------ Markdown
+
+pub fn extract_markdown(input: &str) -> String {
+    // avant le découpage en lignes : un commentaire peut en couvrir plusieurs
+    let input = strip_html_comments(input);
+    let mut out = String::with_capacity(input.len());
+    let mut lines = input.split_inclusive('\n').peekable();
+
+    // front matter YAML (---) ou TOML (+++) en tout début de fichier
+    if let Some(first) = lines.peek()
+        && matches!(first.trim_end(), "---" | "+++")
+    {
+        let fence = first.trim_end().to_string();
+        blank(&mut out, lines.next().unwrap_or_default());
+        for line in lines.by_ref() {
+            blank(&mut out, line);
+            if line.trim_end() == fence {
+                break;
+            }
+        }
+    }
+
+    let mut fence: Option<String> = None;
+    for line in lines {
+        let trimmed = line.trim_start();
+
+        if let Some(f) = &fence {
+            if trimmed.trim_end().starts_with(f.as_str()) {
+                fence = None;
+            }
+            blank(&mut out, line);
+            continue;
+        }
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            let marker = trimmed.chars().next().unwrap_or('`');
+            let len = trimmed.chars().take_while(|&c| c == marker).count();
+            fence = Some(marker.to_string().repeat(len));
+            blank(&mut out, line);
+            continue;
+        }
+
+        // bloc de code indenté (4 espaces ou tabulation)
+        if (line.starts_with("    ") || line.starts_with('\t')) && !trimmed.trim().is_empty() {
+            blank(&mut out, line);
+            continue;
+        }
+
+        // définition de lien : [id]: https://...
+        if trimmed.starts_with('[') && trimmed.contains("]:") {
+            blank(&mut out, line);
+            continue;
+        }
+
+        // le HTML en ligne est traité comme en mode html
+        markdown_inline(&mut out, &extract_html(line));
+    }
+
+    out
+}
+
+/// Retire les commentaires `<!-- ... -->` (un commentaire non fermé court
+/// jusqu'à la fin, comme en mode html) en ne gardant que leurs retours à la
+/// ligne. Pas de blancs à leur place : la fin d'un commentaire deviendrait
+/// une ligne indentée, lue comme du code.
+fn strip_html_comments(input: &str) -> Cow<'_, str> {
+    if !input.contains("<!--") {
+        return Cow::Borrowed(input);
+    }
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find("<!--") {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let end = rest.find("-->").map_or(rest.len(), |i| i + 3);
+        out.extend(rest[..end].chars().filter(|&c| c == '\n'));
+        rest = &rest[end..];
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+/// Retire le code en ligne, les URL des liens/images et les autoliens
+fn markdown_inline(out: &mut String, line: &str) {
+    let mut rest = line;
+    while let Some(i) = rest.find(['`', ']', 'h']) {
+        let (before, at) = rest.split_at(i);
+        out.push_str(before);
+
+        if at.starts_with('`') {
+            let ticks = at.chars().take_while(|&c| c == '`').count();
+            let delim = &at[..ticks];
+            match at[ticks..].find(delim) {
+                Some(end) => {
+                    blank(out, &at[..ticks + end + ticks]);
+                    rest = &at[ticks + end + ticks..];
+                }
+                None => {
+                    out.push_str(delim);
+                    rest = &at[ticks..];
+                }
+            }
+        } else if at.starts_with("](") {
+            // [texte](url "titre") : on garde le texte, on retire l'URL
+            let end = at.find(')').map_or(at.len(), |e| e + 1);
+            blank(out, &at[..end]);
+            rest = &at[end..];
+        } else if at.starts_with("http://") || at.starts_with("https://") {
+            let end = at
+                .find(|c: char| c.is_whitespace() || c == '>' || c == ')')
+                .unwrap_or(at.len());
+            blank(out, &at[..end]);
+            rest = &at[end..];
+        } else {
+            let c = at.chars().next().unwrap_or_default();
+            out.push(c);
+            rest = &at[c.len_utf8()..];
+        }
+    }
+    out.push_str(rest);
+}
+
+// -------------------------------------------------------- code-comments
+
+/// Garde les commentaires (`//`, `#`, `--`, `/* */`, `<!-- -->`, `"""`) et les
+/// chaînes littérales ; tout le reste du code est blanchi.
+pub fn extract_code_comments(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while !rest.is_empty() {
+        // commentaires de bloc et docstrings Python
+        if let Some((open, close)) = [("/*", "*/"), ("<!--", "-->"), ("\"\"\"", "\"\"\"")]
+            .into_iter()
+            .find(|(open, _)| rest.starts_with(open))
+        {
+            let body_end = rest[open.len()..]
+                .find(close)
+                .map_or(rest.len(), |i| open.len() + i);
+            blank(&mut out, open);
+            out.push_str(&rest[open.len()..body_end]);
+            let end = (body_end + close.len()).min(rest.len());
+            blank(&mut out, &rest[body_end..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        // commentaires de ligne ; `#` et `--` seulement en début de mot pour
+        // ne pas prendre les `#[attr]` Rust, les couleurs CSS ou un `i-- ;`
+        // (début de mot lu dans l'entrée : dans `out`, le code blanchi
+        // n'est plus que des espaces)
+        let prev = input[..input.len() - rest.len()].chars().next_back();
+        let word_start = matches!(prev, None | Some(' ' | '\t' | '\n' | '\r'));
+        let line_comment = rest.starts_with("//")
+            || (rest.starts_with("-- ") && word_start)
+            || (rest.starts_with('#')
+                && !rest.starts_with("#[")
+                && !rest.starts_with("#!")
+                && !is_preprocessor(rest)
+                && word_start);
+        if line_comment {
+            let end = rest.find('\n').unwrap_or(rest.len());
+            let marker = rest.chars().take_while(|&c| "/-#!".contains(c)).count();
+            blank(&mut out, &rest[..marker]);
+            out.push_str(&rest[marker..end]);
+            rest = &rest[end..];
+            continue;
+        }
+
+        let c = rest.chars().next().unwrap_or_default();
+        // `'a` / `'static` après `&` ou `<` : durée de vie Rust, pas une chaîne
+        let lifetime = c == '\'' && matches!(prev, Some('&' | '<'));
+        if (c == '"' || c == '\'' || c == '`') && !lifetime {
+            match string_literal_end(rest, c) {
+                Some(end) => {
+                    out.push(' ');
+                    out.push_str(&rest[1..end - 1]);
+                    out.push(' ');
+                    rest = &rest[end..];
+                    continue;
+                }
+                None => {
+                    out.push(' ');
+                    rest = &rest[1..];
+                    continue;
+                }
+            }
+        }
+
+        blank(&mut out, &rest[..c.len_utf8()]);
+        rest = &rest[c.len_utf8()..];
+    }
+
+    out
+}
+
+/// Directives du préprocesseur C (`#include`, `#define`...)
+fn is_preprocessor(s: &str) -> bool {
+    const DIRECTIVES: &[&str] = &[
+        "include", "define", "undef", "ifdef", "ifndef", "if", "elif", "else", "endif", "pragma",
+    ];
+    let word: String = s[1..]
+        .chars()
+        .take_while(|c| c.is_ascii_alphabetic())
+        .collect();
+    DIRECTIVES.contains(&word.as_str())
+}
+
+/// Fin (après le guillemet fermant) d'une chaîne littérale sur une ligne
+/// (les template strings JS entre backquotes peuvent couvrir plusieurs lignes).
+fn string_literal_end(s: &str, quote: char) -> Option<usize> {
+    let mut escaped = false;
+    for (i, c) in s.char_indices().skip(1) {
+        if c == '\n' && quote != '`' {
+            return None;
+        }
+        if escaped {
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == quote {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(s: &str) -> Vec<&str> {
+        s.split_whitespace().collect()
+    }
+
+    #[test]
+    fn html_strips_tags_scripts_and_entities() {
+        let html = "<html><head><style>p { color: red }</style>\n\
+                    <script>var x = \"<b>\";</script></head>\n\
+                    <body><p class=\"a>b\">Caf&eacute; &amp; cr&#xE8;me</p><!-- hidden --></body>";
+        let text = extract_html(html);
+
+        assert_eq!(words(&text), vec!["Café", "&", "crème"]);
+        assert_eq!(text.lines().count(), html.lines().count());
+    }
+
+    #[test]
+    fn markdown_drops_code_urls_and_front_matter() {
+        let md = "---\ntitle: Secret\n---\n# Guide\n\
+                  See [the docs](https://example.com/path/page) and `inline_code`.\n\
+                  ```rust\nfn hidden() {}\n```\n\
+                  [ref]: https://example.com/ref\n    indented_code();\n\
+                  Visit https://example.org today.\n";
+        let text = extract_markdown(md);
+
+        assert_eq!(
+            words(&text),
+            vec![
+                "#", "Guide", "See", "[the", "docs", "and", ".", "Visit", "today."
+            ]
+        );
+        assert_eq!(text.lines().count(), md.lines().count());
+    }
+
+    #[test]
+    fn markdown_drops_multiline_html_comments() {
+        let md = "Intro\n<!--\nTODO hidden\ndraft -->Shown\n\n<!-- open\nto the end";
+        let text = extract_markdown(md);
+
+        assert_eq!(words(&text), vec!["Intro", "Shown"]);
+        assert_eq!(text.matches('\n').count(), md.matches('\n').count());
+    }
+
+    #[test]
+    fn code_comments_keeps_comments_and_strings() {
+        let code = "// the parser\nfn main() { let s = \"hello world\"; }\n\
+                    /* block\ncomment */ x = 1 # python note\n#[derive(Debug)]\n\
+                    #include <stdio.h>\nfn f<'a>(s: &'a str) -> &'a str { s }\n";
+        let text = extract_code_comments(code);
+
+        assert_eq!(
+            words(&text),
+            vec![
+                "the", "parser", "hello", "world", "block", "comment", "python", "note"
+            ]
+        );
+        assert_eq!(text.lines().count(), code.lines().count());
+    }
+
+    #[test]
+    fn line_comment_markers_start_a_word() {
+        let code =
+            "for (;;) { i-- ; n--; }\np { color:#fff }\n-- sql note\nSELECT a -- trailing note\n";
+        assert_eq!(
+            words(&extract_code_comments(code)),
+            vec!["sql", "note", "trailing", "note"]
+        );
+    }
+}
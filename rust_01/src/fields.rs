@@ -0,0 +1,182 @@
+//! Comptage des valeurs d'une colonne (`--field`) : lignes découpées sur un
+//! délimiteur, sur les blancs comme `awk`, ou lues en CSV avec guillemets.
+
+use std::borrow::Cow;
+
+/// Façon de découper chaque enregistrement en champs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delimiter {
+    /// Suites de blancs, blancs de début et de fin ignorés (comme `awk`)
+    Whitespace,
+    /// Un caractère, sans guillemets (comme `cut -d`)
+    Char(char),
+    /// CSV (RFC 4180) : champs entre guillemets, `""` pour un guillemet,
+    /// retours à la ligne possibles dans un champ
+    Csv(char),
+}
+
+/// Lit un délimiteur donné en ligne de commande : un caractère, `\t`,
+/// `tab` ou `space`
+pub fn parse_delimiter(s: &str) -> Result<char, String> {
+    match s {
+        "\\t" | "tab" => return Ok('\t'),
+        "space" => return Ok(' '),
+        _ => {}
+    }
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Ok(c),
+        _ => Err(format!(
+            "invalid delimiter '{s}' (expected a single character, \\t, tab or space)"
+        )),
+    }
+}
+
+/// La valeur du champ `field` (à partir de 1) de chaque enregistrement ;
+/// les enregistrements trop courts et les champs vides (ou faits de blancs)
+/// sont ignorés
+pub fn values(text: &str, field: usize, delimiter: Delimiter) -> Vec<Cow<'_, str>> {
+    let Some(index) = field.checked_sub(1) else {
+        return Vec::new();
+    };
+
+    let mut values: Vec<Cow<str>> = match delimiter {
+        Delimiter::Whitespace => text
+            .lines()
+            .filter_map(|l| l.split_whitespace().nth(index))
+            .map(Cow::Borrowed)
+            .collect(),
+        Delimiter::Char(d) => text
+            .lines()
+            .filter(|l| !l.is_empty())
+            .filter_map(|l| l.trim_end_matches('\r').split(d).nth(index))
+            .map(Cow::Borrowed)
+            .collect(),
+        Delimiter::Csv(d) => csv_records(text, d)
+            .into_iter()
+            .filter_map(|mut r| (index < r.len()).then(|| r.swap_remove(index)))
+            .collect(),
+    };
+    values.retain(|v| !v.trim().is_empty());
+    values
+}
+
+/// Découpe un texte CSV en enregistrements. Les lignes vides sont ignorées ;
+/// un guillemet non fermé court jusqu'à la fin du texte.
+pub fn csv_records(text: &str, delimiter: char) -> Vec<Vec<Cow<'_, str>>> {
+    let mut records = Vec::new();
+    let mut record: Vec<Cow<str>> = Vec::new();
+    let mut chars = text.char_indices().peekable();
+
+    loop {
+        // début d'un champ
+        let field: Cow<str> = if let Some(&(_, '"')) = chars.peek() {
+            chars.next();
+            let mut value = String::new();
+            while let Some((_, c)) = chars.next() {
+                if c == '"' {
+                    if let Some(&(_, '"')) = chars.peek() {
+                        chars.next();
+                        value.push('"');
+                    } else {
+                        break;
+                    }
+                } else {
+                    value.push(c);
+                }
+            }
+            // ce qui suit le guillemet fermant, jusqu'au délimiteur, est ignoré
+            while let Some(&(_, c)) = chars.peek() {
+                if c == delimiter || c == '\n' || c == '\r' {
+                    break;
+                }
+                chars.next();
+            }
+            Cow::Owned(value)
+        } else {
+            let start = chars.peek().map_or(text.len(), |&(i, _)| i);
+            let mut end = text.len();
+            while let Some(&(i, c)) = chars.peek() {
+                if c == delimiter || c == '\n' || c == '\r' {
+                    end = i;
+                    break;
+                }
+                chars.next();
+            }
+            Cow::Borrowed(&text[start..end])
+        };
+        record.push(field);
+
+        match chars.next() {
+            Some((_, c)) if c == delimiter => {}
+            Some((_, '\r')) => {
+                if let Some(&(_, '\n')) = chars.peek() {
+                    chars.next();
+                }
+                end_record(&mut records, &mut record);
+            }
+            Some(_) => end_record(&mut records, &mut record),
+            None => {
+                end_record(&mut records, &mut record);
+                return records;
+            }
+        }
+    }
+}
+
+fn end_record<'a>(records: &mut Vec<Vec<Cow<'a, str>>>, record: &mut Vec<Cow<'a, str>>) {
+    let blank = record.len() == 1 && record[0].is_empty();
+    let record = std::mem::take(record);
+    if !blank {
+        records.push(record);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn whitespace_and_char_delimiters() {
+        let log = "1.2.3.4 - GET /a 200\n  5.6.7.8  - POST /b 404\nshort\n";
+        assert_eq!(values(log, 3, Delimiter::Whitespace), vec!["GET", "POST"]);
+
+        let tsv = "a\tb\tc\r\nd\t\tf\n\ng\n";
+        assert_eq!(values(tsv, 2, Delimiter::Char('\t')), vec!["b"]);
+        assert!(values(tsv, 0, Delimiter::Char('\t')).is_empty());
+    }
+
+    #[test]
+    fn csv_quoting() {
+        let csv = "name,comment\r\nann,\"hello, \"\"world\"\"\"\nbob,\"two\nlines\"\n\nzoe,\n";
+        let records = csv_records(csv, ',');
+
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[1], vec!["ann", "hello, \"world\""]);
+        assert_eq!(records[2], vec!["bob", "two\nlines"]);
+        assert_eq!(records[3], vec!["zoe", ""]);
+        assert_eq!(
+            values(csv, 1, Delimiter::Csv(',')),
+            vec!["name", "ann", "bob", "zoe"]
+        );
+    }
+
+    #[test]
+    fn empty_fields_are_skipped() {
+        let csv = "a,x\nb,\nc,\"\"\nd,\"  \"\ne,x\n";
+        assert_eq!(values(csv, 2, Delimiter::Csv(',')), vec!["x", "x"]);
+        assert_eq!(
+            values("a;;c\n;\n", 2, Delimiter::Char(';')),
+            Vec::<Cow<str>>::new()
+        );
+    }
+
+    #[test]
+    fn delimiters_from_cli() {
+        assert_eq!(parse_delimiter(";"), Ok(';'));
+        assert_eq!(parse_delimiter("\\t"), Ok('\t'));
+        assert_eq!(parse_delimiter("tab"), Ok('\t'));
+        assert!(parse_delimiter("::").is_err());
+        assert!(parse_delimiter("").is_err());
+    }
+}
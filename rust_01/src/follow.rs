@@ -0,0 +1,281 @@
+//! Mode `--follow` : lecture continue d'un fichier qui grandit (comme
+//! `tail -f`) et compteurs "vivants" avec fenêtre glissante ou décroissance.
+
+use crate::tokenizer::Tokenizer;
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, Metadata};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Ce que l'on garde des anciennes lignes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Retention {
+    /// Tout depuis le démarrage
+    All,
+    /// Seulement les mots vus pendant la dernière `Duration`
+    Window(Duration),
+    /// Chaque occurrence perd la moitié de son poids toutes les `half_life`
+    Decay { half_life: Duration },
+}
+
+/// Compteurs mis à jour par lots successifs de texte
+#[derive(Debug, Clone)]
+pub struct LiveCounter {
+    tokenizer: Tokenizer,
+    retention: Retention,
+    counts: HashMap<String, f64>,
+    /// lots encore dans la fenêtre (mode `Window`)
+    batches: VecDeque<(Instant, HashMap<String, usize>)>,
+    last_tick: Option<Instant>,
+}
+
+impl LiveCounter {
+    pub fn new(tokenizer: Tokenizer, retention: Retention) -> Self {
+        Self {
+            tokenizer,
+            retention,
+            counts: HashMap::new(),
+            batches: VecDeque::new(),
+            last_tick: None,
+        }
+    }
+
+    /// Ajoute un lot de texte reçu à l'instant `now`
+    pub fn feed(&mut self, text: &str, now: Instant) {
+        self.advance(now);
+
+        let mut batch: HashMap<String, usize> = HashMap::new();
+        for w in self.tokenizer.words(text) {
+            *batch.entry(w.into_owned()).or_insert(0) += 1;
+        }
+        for (w, c) in &batch {
+            *self.counts.entry(w.clone()).or_insert(0.0) += *c as f64;
+        }
+
+        if matches!(self.retention, Retention::Window(_)) && !batch.is_empty() {
+            self.batches.push_back((now, batch));
+        }
+    }
+
+    /// Fait vieillir les compteurs jusqu'à `now` (fenêtre ou décroissance)
+    pub fn advance(&mut self, now: Instant) {
+        match self.retention {
+            Retention::All => {}
+            Retention::Window(window) => {
+                while let Some((at, _)) = self.batches.front()
+                    && now.saturating_duration_since(*at) > window
+                {
+                    let Some((_, old)) = self.batches.pop_front() else {
+                        break;
+                    };
+                    for (w, c) in old {
+                        if let Some(v) = self.counts.get_mut(&w) {
+                            *v -= c as f64;
+                            if *v <= 0.0 {
+                                self.counts.remove(&w);
+                            }
+                        }
+                    }
+                }
+            }
+            Retention::Decay { half_life } => {
+                if let Some(last) = self.last_tick {
+                    let dt = now.saturating_duration_since(last).as_secs_f64();
+                    let factor = 0.5f64.powf(dt / half_life.as_secs_f64().max(1e-9));
+                    self.counts.retain(|_, v| {
+                        *v *= factor;
+                        // on oublie les mots devenus négligeables
+                        *v >= 0.01
+                    });
+                }
+            }
+        }
+        self.last_tick = Some(now);
+    }
+
+    /// Les `n` mots de plus grand poids
+    pub fn top(&self, n: usize) -> Vec<(&str, f64)> {
+        let mut items: Vec<(&str, f64)> =
+            self.counts.iter().map(|(w, c)| (w.as_str(), *c)).collect();
+        items.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        items.truncate(n);
+        items
+    }
+
+    pub fn get(&self, word: &str) -> f64 {
+        self.counts.get(word).copied().unwrap_or(0.0)
+    }
+}
+
+/// Lit `path` ligne à ligne dans un thread, puis attend les nouvelles lignes
+/// comme `tail -f` (y compris après une troncature ou une rotation sur place).
+/// Sans `path`, lit stdin jusqu'à sa fermeture.
+pub fn tail(path: Option<PathBuf>, poll: Duration) -> io::Result<Receiver<io::Result<String>>> {
+    let (tx, rx) = mpsc::channel();
+
+    match path {
+        None => {
+            thread::spawn(move || {
+                let stdin = io::stdin();
+                for line in stdin.lock().lines() {
+                    let stop = line.is_err();
+                    if tx.send(line.map(|l| l + "\n")).is_err() || stop {
+                        break;
+                    }
+                }
+            });
+        }
+        Some(path) => {
+            let file = File::open(&path)?;
+            thread::spawn(move || {
+                let mut reader = BufReader::new(file);
+                let mut pos = 0u64;
+                let mut buf = Vec::new();
+
+                loop {
+                    buf.clear();
+                    let res = reader.read_until(b'\n', &mut buf).and_then(|n| {
+                        if n > 0 && buf.ends_with(b"\n") {
+                            pos += n as u64;
+                            return Ok(Some(String::from_utf8_lossy(&buf).into_owned()));
+                        }
+
+                        // fin de fichier (ou ligne incomplète) : on revient au
+                        // début de la ligne et on regarde ce qu'est devenu `path`
+                        reader.seek(SeekFrom::Start(pos))?;
+                        match std::fs::metadata(&path) {
+                            Ok(meta)
+                                if file_id(&meta) != file_id(&reader.get_ref().metadata()?) =>
+                            {
+                                // rotation (`mv f f.1` puis nouveau `f`) : l'ancien
+                                // fichier est lu jusqu'au bout, on passe au nouveau
+                                reader = BufReader::new(File::open(&path)?);
+                                pos = 0;
+                                if n > 0 {
+                                    let last = String::from_utf8_lossy(&buf).into_owned();
+                                    return Ok(Some(last + "\n"));
+                                }
+                                return Ok(None);
+                            }
+                            Ok(meta) if meta.len() < pos => {
+                                // même fichier, tronqué sur place : on repart du début
+                                pos = 0;
+                                reader.seek(SeekFrom::Start(0))?;
+                            }
+                            // renommé mais pas encore recréé : on attend
+                            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                            Err(e) => return Err(e),
+                            Ok(_) => {}
+                        }
+                        thread::sleep(poll);
+                        Ok(None)
+                    });
+
+                    let msg = match res {
+                        Ok(None) => continue,
+                        Ok(Some(line)) => Ok(line),
+                        Err(e) => Err(e),
+                    };
+                    let stop = msg.is_err();
+                    if tx.send(msg).is_err() || stop {
+                        break;
+                    }
+                }
+            });
+        }
+    }
+
+    Ok(rx)
+}
+
+/// Identité d'un fichier (périphérique, inode) : elle change quand `path`
+/// désigne un nouveau fichier après une rotation
+#[cfg(unix)]
+fn file_id(meta: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((meta.dev(), meta.ino()))
+}
+
+/// Sans inode, seule la troncature sur place est détectée
+#[cfg(not(unix))]
+fn file_id(_meta: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sliding_window_forgets_old_batches() {
+        let t0 = Instant::now();
+        let mut live =
+            LiveCounter::new(Tokenizer::new(), Retention::Window(Duration::from_secs(60)));
+
+        live.feed("error timeout error", t0);
+        live.feed("error disk", t0 + Duration::from_secs(30));
+        assert_eq!(live.get("error"), 3.0);
+
+        live.advance(t0 + Duration::from_secs(61));
+        assert_eq!(live.get("error"), 1.0);
+        assert_eq!(live.get("timeout"), 0.0);
+        assert_eq!(live.top(2), vec![("disk", 1.0), ("error", 1.0)]);
+    }
+
+    #[test]
+    fn decay_halves_weights_every_half_life() {
+        let t0 = Instant::now();
+        let mut live = LiveCounter::new(
+            Tokenizer::new(),
+            Retention::Decay {
+                half_life: Duration::from_secs(10),
+            },
+        );
+
+        live.feed("a a a a", t0);
+        live.advance(t0 + Duration::from_secs(10));
+        assert!((live.get("a") - 2.0).abs() < 1e-9);
+
+        live.feed("b", t0 + Duration::from_secs(20));
+        assert!((live.get("a") - 1.0).abs() < 1e-9);
+        assert_eq!(live.top(2)[0].0, "a");
+    }
+
+    /// Les lignes reçues jusqu'à ce que `rx` reste muet 300 ms
+    fn drain(rx: &Receiver<io::Result<String>>) -> Vec<String> {
+        let mut lines = Vec::new();
+        while let Ok(line) = rx.recv_timeout(Duration::from_millis(300)) {
+            lines.push(line.unwrap());
+        }
+        lines
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn tail_follows_rotation_and_truncation() {
+        let dir = std::env::temp_dir().join(format!("wordfreq-tail-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("f.log");
+        std::fs::write(&path, "alpha\nbeta\n").unwrap();
+
+        let rx = tail(Some(path.clone()), Duration::from_millis(10)).unwrap();
+        assert_eq!(drain(&rx), vec!["alpha\n", "beta\n"]);
+
+        // rotation par renommage : le nouveau fichier est lu, l'ancien n'est
+        // pas relu
+        std::fs::rename(&path, dir.join("f.log.1")).unwrap();
+        std::fs::write(&path, "gamma\n").unwrap();
+        assert_eq!(drain(&rx), vec!["gamma\n"]);
+
+        // troncature sur place (`> f.log`) : reprise au début
+        std::fs::write(&path, "").unwrap();
+        thread::sleep(Duration::from_millis(200));
+        std::fs::write(&path, "delta\n").unwrap();
+        assert_eq!(drain(&rx), vec!["delta\n"]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,171 @@
+//! Table d'internement des mots : tous les mots distincts sont copiés bout à
+//! bout dans une seule `String` (l'arène) et désignés par un `Symbol`.
+//!
+//! La recherche se fait avec un `&str` emprunté : un mot déjà connu ne coûte
+//! ni allocation ni copie, un mot nouveau coûte une copie dans l'arène (pas
+//! d'allocation par mot). La table est à adressage ouvert (sondage linéaire)
+//! et ne contient que des indices.
+
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+/// Identifiant d'un mot interné, stable tant que l'`Interner` existe
+pub type Symbol = u32;
+
+const EMPTY: u32 = u32::MAX;
+
+#[derive(Debug, Clone)]
+pub struct Interner {
+    arena: String,
+    /// (début, fin) de chaque symbole dans l'arène
+    spans: Vec<(usize, usize)>,
+    hashes: Vec<u64>,
+    /// symboles, ou `EMPTY` ; la taille est une puissance de 2
+    table: Vec<u32>,
+    seed: u64,
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self {
+            arena: String::new(),
+            spans: Vec::new(),
+            hashes: Vec::new(),
+            table: vec![EMPTY; 16],
+            // graine aléatoire par processus : un texte forgé ne peut pas
+            // provoquer exprès des collisions en série
+            seed: RandomState::new().hash_one(0u64) | 1,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Hachage multiplicatif 8 octets par 8, bien plus rapide que SipHash
+    /// sur des mots courts
+    fn hash(&self, s: &str) -> u64 {
+        const K: u64 = 0x9e37_79b9_7f4a_7c15;
+        let mut h = self.seed ^ (s.len() as u64).wrapping_mul(K);
+        let mut chunks = s.as_bytes().chunks_exact(8);
+        for c in &mut chunks {
+            let v = u64::from_le_bytes(c.try_into().expect("8 bytes"));
+            h = (h.rotate_left(5) ^ v).wrapping_mul(K);
+        }
+        let mut last = [0u8; 8];
+        let rest = chunks.remainder();
+        last[..rest.len()].copy_from_slice(rest);
+        h = (h.rotate_left(5) ^ u64::from_le_bytes(last)).wrapping_mul(K);
+        h ^ (h >> 29)
+    }
+
+    /// Position dans la table : celle du symbole `s`, ou la case vide où
+    /// l'insérer
+    fn slot(&self, s: &str, hash: u64) -> usize {
+        let mask = self.table.len() - 1;
+        let mut i = hash as usize & mask;
+        loop {
+            let sym = self.table[i];
+            if sym == EMPTY || (self.hashes[sym as usize] == hash && self.resolve(sym) == s) {
+                return i;
+            }
+            i = (i + 1) & mask;
+        }
+    }
+
+    /// Le symbole de `s`, s'il est déjà interné
+    pub fn get(&self, s: &str) -> Option<Symbol> {
+        let sym = self.table[self.slot(s, self.hash(s))];
+        (sym != EMPTY).then_some(sym)
+    }
+
+    /// Le symbole de `s`, en le copiant dans l'arène s'il est nouveau
+    pub fn intern(&mut self, s: &str) -> Symbol {
+        let hash = self.hash(s);
+        let i = self.slot(s, hash);
+        if self.table[i] != EMPTY {
+            return self.table[i];
+        }
+
+        let sym = Symbol::try_from(self.spans.len()).expect("too many distinct words");
+        let start = self.arena.len();
+        self.arena.push_str(s);
+        self.spans.push((start, self.arena.len()));
+        self.hashes.push(hash);
+        self.table[i] = sym;
+
+        // remplissage maximal de 70 %
+        if self.spans.len() * 10 > self.table.len() * 7 {
+            self.grow();
+        }
+        sym
+    }
+
+    fn grow(&mut self) {
+        let size = self.table.len() * 2;
+        let mask = size - 1;
+        let mut table = vec![EMPTY; size];
+        for (sym, &hash) in self.hashes.iter().enumerate() {
+            let mut i = hash as usize & mask;
+            while table[i] != EMPTY {
+                i = (i + 1) & mask;
+            }
+            table[i] = sym as Symbol;
+        }
+        self.table = table;
+    }
+
+    pub fn resolve(&self, sym: Symbol) -> &str {
+        let (start, end) = self.spans[sym as usize];
+        &self.arena[start..end]
+    }
+
+    /// Tous les mots, dans l'ordre d'internement
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (Symbol, &str)> + '_ {
+        self.spans
+            .iter()
+            .enumerate()
+            .map(|(i, &(s, e))| (i as Symbol, &self.arena[s..e]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interning_is_idempotent() {
+        let mut i = Interner::new();
+        let a = i.intern("hello");
+        let b = i.intern("world");
+        assert_ne!(a, b);
+        assert_eq!(i.intern("hello"), a);
+        assert_eq!(i.get("world"), Some(b));
+        assert_eq!(i.get("nope"), None);
+        assert_eq!(i.resolve(b), "world");
+        assert_eq!(i.len(), 2);
+    }
+
+    #[test]
+    fn survives_growth() {
+        let mut i = Interner::new();
+        let words: Vec<String> = (0..10_000).map(|n| format!("w{n}é")).collect();
+        let syms: Vec<Symbol> = words.iter().map(|w| i.intern(w)).collect();
+
+        for (w, s) in words.iter().zip(&syms) {
+            assert_eq!(i.get(w), Some(*s));
+            assert_eq!(i.resolve(*s), w);
+        }
+        assert_eq!(i.iter().len(), 10_000);
+    }
+}
@@ -0,0 +1,193 @@
+//! Concordance "keyword in context" (`--kwic WORD --context N`).
+
+use crate::position::LineIndex;
+use crate::tokenizer::{Token, Tokenizer};
+use std::str::FromStr;
+
+/// Unité du contexte gauche/droite
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ContextUnit {
+    #[default]
+    Tokens,
+    Chars,
+}
+
+impl FromStr for ContextUnit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "tokens" | "token" => Ok(ContextUnit::Tokens),
+            "chars" | "char" => Ok(ContextUnit::Chars),
+            _ => Err(format!(
+                "unknown context unit '{s}' (expected tokens or chars)"
+            )),
+        }
+    }
+}
+
+/// Une occurrence du mot cherché avec son contexte
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KwicLine {
+    pub source: String,
+    pub line: usize,
+    pub column: usize,
+    pub left: String,
+    pub keyword: String,
+    pub right: String,
+}
+
+/// Toutes les occurrences de `word` dans `text`. Le mot est normalisé par
+/// le même `Tokenizer` que le comptage (casse, longueur minimale).
+pub fn concordance(
+    source: &str,
+    text: &str,
+    tokenizer: &Tokenizer,
+    word: &str,
+    context: usize,
+    unit: ContextUnit,
+) -> Vec<KwicLine> {
+    let Some(wanted) = tokenizer.normalize(word) else {
+        return Vec::new();
+    };
+
+    let tokens: Vec<Token> = tokenizer.tokens(text).collect();
+    let lines = LineIndex::new(text);
+    let mut out = Vec::new();
+
+    for (i, t) in tokens.iter().enumerate() {
+        if tokenizer.normalize(t.text).as_deref() != Some(wanted.as_ref()) {
+            continue;
+        }
+
+        let (left, right) = match unit {
+            ContextUnit::Tokens => {
+                let from = tokens[i.saturating_sub(context)].start;
+                let to = tokens[i.saturating_add(context).min(tokens.len() - 1)].end;
+                (&text[from..t.start], &text[t.end..to])
+            }
+            ContextUnit::Chars => {
+                let from = match context {
+                    0 => t.start,
+                    n => text[..t.start]
+                        .char_indices()
+                        .rev()
+                        .nth(n - 1)
+                        .map_or(0, |(i, _)| i),
+                };
+                let to = text[t.end..]
+                    .char_indices()
+                    .nth(context)
+                    .map_or(text.len(), |(i, _)| t.end + i);
+                (&text[from..t.start], &text[t.end..to])
+            }
+        };
+
+        let (line, column) = lines.locate(t.start);
+        out.push(KwicLine {
+            source: source.to_string(),
+            line,
+            column,
+            left: flatten(left),
+            keyword: t.text.to_string(),
+            right: flatten(right),
+        });
+    }
+
+    out
+}
+
+/// Les retours à la ligne et tabulations deviennent des espaces simples
+fn flatten(s: &str) -> String {
+    s.chars()
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .collect()
+}
+
+/// Met en forme les occurrences en colonnes : position, contexte gauche
+/// aligné à droite, mot, contexte droit
+pub fn format_lines(lines: &[KwicLine]) -> Vec<String> {
+    let locations: Vec<String> = lines
+        .iter()
+        .map(|l| format!("{}:{}:{}", l.source, l.line, l.column))
+        .collect();
+    let loc_width = locations
+        .iter()
+        .map(|l| l.chars().count())
+        .max()
+        .unwrap_or(0);
+    let left_width = lines
+        .iter()
+        .map(|l| l.left.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    lines
+        .iter()
+        .zip(&locations)
+        .map(|(l, loc)| {
+            format!(
+                "{loc:<loc_width$}  {:>left_width$}[{}]{}",
+                l.left, l.keyword, l.right
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn token_context_and_positions() {
+        let text = "the quick fox\njumps over the lazy dog";
+        let lines = concordance(
+            "t.txt",
+            text,
+            &Tokenizer::new(),
+            "the",
+            2,
+            ContextUnit::Tokens,
+        );
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!((lines[0].line, lines[0].column), (1, 1));
+        assert_eq!(lines[0].left, "");
+        assert_eq!(lines[0].right, " quick fox");
+        assert_eq!((lines[1].line, lines[1].column), (2, 12));
+        assert_eq!(lines[1].left, "jumps over ");
+        assert_eq!(lines[1].right, " lazy dog");
+    }
+
+    #[test]
+    fn context_larger_than_the_text() {
+        for unit in [ContextUnit::Tokens, ContextUnit::Chars] {
+            let lines = concordance("-", "y x z", &Tokenizer::new(), "x", usize::MAX, unit);
+
+            assert_eq!(lines.len(), 1);
+            assert_eq!(lines[0].left, "y ");
+            assert_eq!(lines[0].right, " z");
+        }
+    }
+
+    #[test]
+    fn char_context_and_ignore_case() {
+        let tok = Tokenizer::new().ignore_case(true);
+        let lines = concordance("-", "abc The xyz", &tok, "THE", 3, ContextUnit::Chars);
+
+        assert_eq!(lines.len(), 1);
+        assert_eq!(lines[0].left, "bc ");
+        assert_eq!(lines[0].keyword, "The");
+        assert_eq!(lines[0].right, " xy");
+    }
+
+    #[test]
+    fn columns_are_aligned() {
+        let text = "a key b\nlonger prefix key";
+        let lines = concordance("f", text, &Tokenizer::new(), "key", 2, ContextUnit::Tokens);
+        let out = format_lines(&lines);
+
+        let pos: Vec<usize> = out.iter().map(|l| l.find("[key]").unwrap()).collect();
+        assert_eq!(pos[0], pos[1]);
+    }
+}
@@ -0,0 +1,311 @@
+//! Identification de la langue (`--detect-language`) à partir de listes de
+//! mots-outils embarquées, réutilisées par `--stopwords`.
+
+/// Une langue connue et ses mots-outils les plus fréquents (en minuscules)
+#[derive(Debug, PartialEq, Eq)]
+pub struct Language {
+    pub code: &'static str,
+    pub name: &'static str,
+    pub stopwords: &'static [&'static str],
+}
+
+impl Language {
+    /// `word` doit déjà être en minuscules
+    pub fn is_stopword(&self, word: &str) -> bool {
+        self.stopwords.contains(&word)
+    }
+}
+
+pub const LANGUAGES: &[Language] = &[
+    Language {
+        code: "en",
+        name: "English",
+        stopwords: &[
+            "the", "be", "to", "of", "and", "a", "in", "that", "have", "i", "it", "for", "not",
+            "on", "with", "he", "as", "you", "do", "at", "this", "but", "his", "by", "from",
+            "they", "we", "her", "she", "or", "an", "will", "my", "all", "would", "there", "their",
+            "what", "so", "if", "about", "who", "which", "when", "can", "just", "him", "into",
+            "your", "some", "could", "them", "than", "then", "its", "also", "is", "are", "was",
+            "were", "has", "had", "been",
+        ],
+    },
+    Language {
+        code: "fr",
+        name: "French",
+        stopwords: &[
+            "le", "la", "les", "de", "des", "du", "un", "une", "et", "est", "en", "que", "qui",
+            "dans", "pour", "pas", "sur", "au", "aux", "avec", "ce", "cette", "il", "elle", "ils",
+            "elles", "nous", "vous", "je", "tu", "on", "ne", "se", "sa", "son", "ses", "leur",
+            "mais", "ou", "où", "par", "plus", "comme", "été", "être", "avoir", "fait", "tout",
+            "très", "aussi", "bien", "y", "a", "sont", "était", "ces", "lui", "même",
+        ],
+    },
+    Language {
+        code: "de",
+        name: "German",
+        stopwords: &[
+            "der", "die", "das", "und", "ist", "nicht", "ein", "eine", "einen", "dem", "den",
+            "des", "zu", "mit", "sich", "auf", "für", "von", "im", "in", "es", "auch", "als", "an",
+            "er", "sie", "wir", "ihr", "ich", "du", "aber", "wie", "oder", "wenn", "noch", "nur",
+            "bei", "aus", "nach", "so", "war", "hat", "sind", "werden", "wird", "kann", "dass",
+            "über", "einem", "einer", "um", "schon", "mehr",
+        ],
+    },
+    Language {
+        code: "es",
+        name: "Spanish",
+        stopwords: &[
+            "el", "la", "los", "las", "de", "del", "y", "en", "que", "un", "una", "es", "por",
+            "con", "para", "no", "se", "su", "sus", "lo", "al", "como", "más", "pero", "le", "ya",
+            "o", "este", "esta", "son", "fue", "ha", "muy", "también", "entre", "cuando", "todo",
+            "ser", "hay", "sobre", "me", "sin", "porque", "desde", "donde", "yo", "está", "están",
+        ],
+    },
+    Language {
+        code: "it",
+        name: "Italian",
+        stopwords: &[
+            "il", "lo", "la", "i", "gli", "le", "di", "del", "della", "dei", "e", "è", "un", "una",
+            "in", "che", "per", "non", "con", "si", "da", "al", "alla", "sono", "come", "ma",
+            "anche", "più", "questo", "questa", "ci", "ne", "ha", "nel", "nella", "essere", "loro",
+            "quando", "mi", "io", "suo", "sua", "ed", "perché", "tutto", "molto", "era", "delle",
+        ],
+    },
+    Language {
+        code: "pt",
+        name: "Portuguese",
+        stopwords: &[
+            "o", "a", "os", "as", "de", "do", "da", "dos", "das", "e", "é", "um", "uma", "em",
+            "que", "para", "com", "não", "por", "se", "no", "na", "mais", "como", "mas", "ao",
+            "seu", "sua", "foi", "são", "ele", "ela", "isso", "também", "já", "muito", "ou",
+            "quando", "nos", "pelo", "pela", "está", "eu", "tem", "sem", "ser", "às", "até",
+        ],
+    },
+    Language {
+        code: "nl",
+        name: "Dutch",
+        stopwords: &[
+            "de", "het", "een", "en", "is", "van", "in", "op", "te", "dat", "die", "niet", "met",
+            "voor", "zijn", "er", "aan", "als", "ook", "maar", "bij", "om", "dan", "nog", "wat",
+            "ze", "hij", "ik", "je", "wij", "zij", "worden", "wordt", "werd", "door", "naar",
+            "heeft", "hebben", "was", "uit", "al", "geen", "kan", "tot", "dit", "deze", "over",
+        ],
+    },
+    Language {
+        code: "sv",
+        name: "Swedish",
+        stopwords: &[
+            "och", "i", "att", "det", "som", "en", "på", "är", "av", "för", "med", "till", "den",
+            "inte", "har", "de", "om", "ett", "var", "jag", "men", "så", "han", "hon", "vi", "ni",
+            "sig", "från", "kan", "eller", "när", "alla", "vid", "också", "efter", "under",
+            "skulle", "vara", "blev", "hade", "detta", "mycket", "sina", "utan", "dem", "då",
+        ],
+    },
+    Language {
+        code: "da",
+        name: "Danish",
+        stopwords: &[
+            "og", "i", "at", "det", "som", "en", "på", "er", "af", "for", "med", "til", "den",
+            "ikke", "har", "de", "om", "et", "var", "jeg", "men", "så", "han", "hun", "vi", "sig",
+            "fra", "kan", "eller", "når", "alle", "ved", "også", "efter", "under", "skulle",
+            "være", "blev", "havde", "dette", "meget", "hvor", "nu", "mig", "hvad", "her",
+        ],
+    },
+    Language {
+        code: "nb",
+        name: "Norwegian",
+        stopwords: &[
+            "og", "i", "å", "det", "som", "en", "på", "er", "av", "for", "med", "til", "den",
+            "ikke", "har", "de", "om", "et", "var", "jeg", "men", "så", "han", "hun", "vi", "seg",
+            "fra", "kan", "eller", "når", "alle", "ved", "også", "etter", "under", "skulle",
+            "være", "ble", "hadde", "dette", "mye", "hvor", "nå", "meg", "hva", "her",
+        ],
+    },
+    Language {
+        code: "fi",
+        name: "Finnish",
+        stopwords: &[
+            "ja", "on", "ei", "se", "että", "oli", "ovat", "hän", "mutta", "kun", "tai", "myös",
+            "joka", "jos", "niin", "kuin", "sen", "tämä", "ole", "olla", "vain", "minä", "sinä",
+            "me", "te", "he", "mitä", "siitä", "kanssa", "sekä", "ollut", "kaikki", "jo", "nyt",
+            "sitä", "hänen", "mukaan", "vielä", "voi", "tässä", "koska", "jotka",
+        ],
+    },
+    Language {
+        code: "pl",
+        name: "Polish",
+        stopwords: &[
+            "i", "w", "nie", "na", "z", "się", "do", "to", "że", "jest", "a", "o", "jak", "po",
+            "ale", "co", "tak", "za", "od", "przez", "jego", "jej", "są", "być", "ten", "ta",
+            "tym", "dla", "już", "tylko", "czy", "może", "był", "była", "oraz", "jako", "też",
+            "gdy", "jednak", "bardzo", "które", "który", "która", "ich", "tego",
+        ],
+    },
+    Language {
+        code: "cs",
+        name: "Czech",
+        stopwords: &[
+            "a", "v", "se", "na", "je", "že", "to", "s", "z", "do", "o", "jako", "ale", "by",
+            "pro", "tak", "jsou", "jeho", "její", "byl", "byla", "být", "který", "která", "které",
+            "po", "za", "od", "ve", "už", "jen", "nebo", "když", "také", "jsem", "není", "jsme",
+            "však", "bylo", "mezi", "před", "jejich", "ještě",
+        ],
+    },
+    Language {
+        code: "hu",
+        name: "Hungarian",
+        stopwords: &[
+            "a", "az", "és", "hogy", "nem", "is", "egy", "meg", "van", "volt", "de", "ez", "azt",
+            "csak", "még", "mint", "ki", "már", "el", "fel", "be", "vagy", "lesz", "kell", "ha",
+            "sem", "minden", "mert", "után", "én", "te", "ő", "mi", "ők", "nagyon", "pedig",
+            "amely", "aki", "ezt", "ami", "itt", "között",
+        ],
+    },
+    Language {
+        code: "ro",
+        name: "Romanian",
+        stopwords: &[
+            "și", "în", "de", "la", "a", "cu", "pe", "nu", "este", "o", "un", "care", "mai", "din",
+            "ce", "se", "sunt", "pentru", "ca", "sau", "dar", "fi", "fost", "au", "ei", "ea",
+            "lui", "acest", "această", "să", "am", "al", "ale", "unei", "unui", "foarte", "când",
+            "doar", "după", "prin", "eu", "noi",
+        ],
+    },
+    Language {
+        code: "tr",
+        name: "Turkish",
+        stopwords: &[
+            "ve", "bir", "bu", "da", "de", "için", "ile", "çok", "ne", "o", "gibi", "daha", "ama",
+            "en", "var", "mı", "mi", "ben", "sen", "biz", "onlar", "olan", "olarak", "değil",
+            "kadar", "şey", "her", "ya", "ki", "bunu", "şu", "sonra", "diye", "yok", "çünkü",
+            "veya", "hem", "ise", "göre",
+        ],
+    },
+    Language {
+        code: "id",
+        name: "Indonesian",
+        stopwords: &[
+            "yang", "dan", "di", "ke", "dari", "ini", "itu", "dengan", "untuk", "tidak", "ada",
+            "dalam", "akan", "pada", "juga", "saya", "kami", "mereka", "atau", "sudah", "bisa",
+            "oleh", "karena", "adalah", "kita", "anda", "telah", "seperti", "lebih", "hanya",
+            "sangat", "belum", "masih", "harus", "jika", "tetapi",
+        ],
+    },
+];
+
+/// Cherche une langue par son code ("en", "fr", ...)
+pub fn by_code(code: &str) -> Option<&'static Language> {
+    let code = code.to_ascii_lowercase();
+    let code = match code.as_str() {
+        "no" | "nn" => "nb",
+        other => other,
+    };
+    LANGUAGES.iter().find(|l| l.code == code)
+}
+
+/// Score d'une langue : part des mots-outils parmi les concordances
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Guess {
+    pub language: &'static Language,
+    /// entre 0 et 1 ; la somme sur toutes les langues vaut 1
+    pub confidence: f64,
+    /// nombre de mots du texte trouvés dans la liste de la langue
+    pub hits: usize,
+}
+
+/// En dessous, le texte est trop court pour conclure
+const MIN_HITS: usize = 3;
+
+/// Classe les langues de la plus probable à la moins probable. Renvoie une
+/// liste vide si le texte ne contient pas assez de mots-outils connus.
+pub fn detect(text: &str) -> Vec<Guess> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|w| !w.is_empty())
+        .map(str::to_lowercase)
+        .collect();
+
+    let mut guesses: Vec<Guess> = LANGUAGES
+        .iter()
+        .map(|language| Guess {
+            language,
+            confidence: 0.0,
+            hits: words.iter().filter(|w| language.is_stopword(w)).count(),
+        })
+        .filter(|g| g.hits > 0)
+        .collect();
+
+    let best = guesses.iter().map(|g| g.hits).max().unwrap_or(0);
+    if best < MIN_HITS {
+        return Vec::new();
+    }
+
+    // les langues proches partagent beaucoup de mots-outils : on accentue
+    // l'écart avec un carré avant de normaliser
+    let total: f64 = guesses.iter().map(|g| (g.hits as f64).powi(2)).sum();
+    for g in &mut guesses {
+        g.confidence = (g.hits as f64).powi(2) / total;
+    }
+
+    guesses.sort_by(|a, b| {
+        b.hits
+            .cmp(&a.hits)
+            .then_with(|| a.language.code.cmp(b.language.code))
+    });
+    guesses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn best(text: &str) -> &'static str {
+        detect(text)[0].language.code
+    }
+
+    #[test]
+    fn detects_common_languages() {
+        assert_eq!(
+            best("The cat is on the table and it was sleeping when we came in."),
+            "en"
+        );
+        assert_eq!(
+            best("Le chat est sur la table et il dormait quand nous sommes arrivés."),
+            "fr"
+        );
+        assert_eq!(
+            best("Die Katze ist auf dem Tisch und sie schläft, wenn wir nach Hause kommen."),
+            "de"
+        );
+        assert_eq!(
+            best("El gato está en la mesa y duerme cuando llegamos a casa con los niños."),
+            "es"
+        );
+        assert_eq!(
+            best("Katten ligger på bordet och sover när vi kommer hem, men den är inte hungrig."),
+            "sv"
+        );
+    }
+
+    #[test]
+    fn too_short_is_unknown() {
+        assert!(detect("hello").is_empty());
+        assert!(detect("").is_empty());
+    }
+
+    #[test]
+    fn confidences_sum_to_one() {
+        let g = detect("the cat and the dog are in the house");
+        let sum: f64 = g.iter().map(|g| g.confidence).sum();
+        assert!((sum - 1.0).abs() < 1e-9);
+        assert!(g[0].confidence > 0.5);
+    }
+
+    #[test]
+    fn language_codes() {
+        assert_eq!(by_code("FR").unwrap().name, "French");
+        assert_eq!(by_code("no").unwrap().code, "nb");
+        assert!(by_code("xx").is_none());
+        assert!(LANGUAGES.len() >= 15);
+    }
+}
@@ -0,0 +1,39 @@
+//! Comptage de fréquence de mots, utilisable comme bibliothèque
+//! (le binaire `wordfreq` n'est qu'une interface en ligne de commande).
+//!
+//! ```
+//! use wordfreq::{Tokenizer, WordCounter};
+//!
+//! let mut counter = WordCounter::with_tokenizer(Tokenizer::new().ignore_case(true));
+//! counter.feed("Hello world");
+//! counter.feed("hello again");
+//!
+//! assert_eq!(counter.top(1), vec![("hello", 2)]);
+//! ```
+
+pub mod approx;
+pub mod code;
+pub mod collocation;
+pub mod counter;
+pub mod decode;
+pub mod extract;
+pub mod fields;
+pub mod follow;
+pub mod intern;
+pub mod kwic;
+pub mod language;
+pub mod locale;
+pub mod matrix;
+pub mod position;
+pub mod readability;
+pub mod server;
+pub mod snapshot;
+pub mod spell;
+pub mod timeline;
+pub mod tokenizer;
+pub mod variants;
+pub mod wordcloud;
+
+pub use counter::WordCounter;
+pub use locale::{Collator, LocaleSettings};
+pub use tokenizer::{Token, Tokenizer, Unit};
@@ -0,0 +1,198 @@
+use num_format::Locale;
+use std::cmp::Ordering;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+/// Lettres qui, dans certaines langues, sont des lettres à part entière
+/// triées après une lettre de base (ex: en suédois "å", "ä", "ö" après "z").
+/// Chaque entrée : (lettre minuscule, lettre d'ancrage, rang après l'ancre).
+type Tailoring = &'static [(char, char, u8)];
+
+const NORDIC_SV_FI: Tailoring = &[('å', 'z', 1), ('ä', 'z', 2), ('ö', 'z', 3)];
+const NORDIC_DA_NB: Tailoring = &[('æ', 'z', 1), ('ø', 'z', 2), ('å', 'z', 3)];
+const SPANISH: Tailoring = &[('ñ', 'n', 1)];
+
+/// Poids secondaire d'une lettre de base, sans accent
+const COMMON: u32 = 1;
+
+/// Poids secondaires des diacritiques courants, dans l'ordre de la table
+/// DUCET (aigu avant grave, "é" < "è" < "ê" < "ë"...)
+const ACCENTS: &[(char, u32)] = &[
+    ('\u{301}', 2),  // aigu
+    ('\u{300}', 3),  // grave
+    ('\u{306}', 4),  // brève
+    ('\u{302}', 5),  // circonflexe
+    ('\u{30c}', 6),  // caron
+    ('\u{30a}', 7),  // rond en chef
+    ('\u{308}', 8),  // tréma
+    ('\u{30b}', 9),  // double aigu
+    ('\u{303}', 10), // tilde
+    ('\u{307}', 11), // point en chef
+    ('\u{327}', 12), // cédille
+    ('\u{328}', 13), // ogonek
+    ('\u{304}', 14), // macron
+    ('\u{323}', 15), // point souscrit
+];
+
+/// Poids secondaire d'un diacritique ; les autres suivent la table, dans
+/// l'ordre de leur point de code
+fn accent_weight(mark: char) -> u32 {
+    match ACCENTS.iter().find(|(c, _)| *c == mark) {
+        Some(&(_, w)) => w,
+        None => 0x100 + mark as u32,
+    }
+}
+
+/// Locale choisie par `--locale` : séparateurs de milliers + collation
+#[derive(Debug, Clone)]
+pub struct LocaleSettings {
+    pub number: Locale,
+    pub collator: Collator,
+}
+
+impl LocaleSettings {
+    /// Parse un nom de locale ("en", "fr", "de-CH", "sv"...)
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        let number = Locale::from_name(name).map_err(|_| format!("unknown locale '{name}'"))?;
+        Ok(Self {
+            number,
+            collator: Collator::for_locale(name),
+        })
+    }
+}
+
+/// Comparaison de chaînes "à la locale" : on compare d'abord les lettres de
+/// base (sans accents ni casse), puis les accents, puis la casse, et enfin
+/// les octets pour garder un ordre total et stable.
+#[derive(Debug, Clone, Default)]
+pub struct Collator {
+    tailoring: Tailoring,
+}
+
+impl Collator {
+    pub fn for_locale(name: &str) -> Self {
+        let lang = name
+            .split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        let tailoring = match lang.as_str() {
+            "sv" | "fi" => NORDIC_SV_FI,
+            "da" | "nb" | "nn" | "no" => NORDIC_DA_NB,
+            "es" => SPANISH,
+            _ => &[],
+        };
+
+        Self { tailoring }
+    }
+
+    pub fn compare(&self, a: &str, b: &str) -> Ordering {
+        self.sort_key(a)
+            .cmp(&self.sort_key(b))
+            .then_with(|| a.cmp(b))
+    }
+
+    /// Clé de tri de `s`, à calculer une seule fois par mot pour trier une
+    /// longue liste (`sort_by_cached_key`) ; à clés égales, comparer les
+    /// octets comme le fait [`Collator::compare`]
+    pub fn sort_key(&self, s: &str) -> SortKey {
+        let mut key = SortKey::default();
+
+        for c in s.chars() {
+            let lower = c.to_lowercase().next().unwrap_or(c);
+            // minuscules avant majuscules, comme la plupart des locales
+            let case = u8::from(lower != c);
+
+            if let Some(&(_, anchor, rank)) = self.tailoring.iter().find(|(l, _, _)| *l == lower) {
+                key.primary.push((anchor, rank));
+                key.secondary.push(COMMON);
+                key.tertiary.push(case);
+                continue;
+            }
+
+            // comme en UCA : chaque lettre de base puis chacun de ses accents
+            // (dans l'ordre canonique de NFD) ajoutent un poids secondaire
+            for d in c.nfd() {
+                if is_combining_mark(d) {
+                    key.secondary.push(accent_weight(d));
+                } else {
+                    let base = d.to_lowercase().next().unwrap_or(d);
+                    key.primary.push((base, 0));
+                    key.secondary.push(COMMON);
+                    key.tertiary.push(case);
+                }
+            }
+        }
+
+        key
+    }
+}
+
+/// Clé de tri : lettres de base, puis accents, puis casse (l'ordre des
+/// champs est celui de la comparaison)
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SortKey {
+    primary: Vec<(char, u8)>,
+    secondary: Vec<u32>,
+    tertiary: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sorted(locale: &str, words: &[&str]) -> Vec<String> {
+        let collator = Collator::for_locale(locale);
+        let mut v: Vec<String> = words.iter().map(|w| w.to_string()).collect();
+        v.sort_by(|a, b| collator.compare(a, b));
+        v
+    }
+
+    #[test]
+    fn accented_letters_sort_next_to_base_letter() {
+        // En ordre d'octets "été" serait après "zèbre"
+        assert_eq!(
+            sorted("fr", &["zèbre", "été", "ete", "etre", "abc"]),
+            vec!["abc", "ete", "été", "etre", "zèbre"]
+        );
+    }
+
+    #[test]
+    fn accents_follow_ducet_order() {
+        // aigu < grave < circonflexe < tréma, comme en CLDR
+        assert_eq!(
+            sorted("fr", &["eë", "eê", "eè", "eé", "ee"]),
+            vec!["ee", "eé", "eè", "eê", "eë"]
+        );
+        // le premier accent qui diffère décide, quel que soit le nombre
+        assert_eq!(
+            sorted("fr", &["élève", "élevé", "élevè", "eleve"]),
+            vec!["eleve", "élevé", "élevè", "élève"]
+        );
+    }
+
+    #[test]
+    fn case_is_a_late_tie_break() {
+        assert_eq!(
+            sorted("en", &["b", "B", "a", "A"]),
+            vec!["a", "A", "b", "B"]
+        );
+    }
+
+    #[test]
+    fn swedish_letters_sort_after_z() {
+        assert_eq!(
+            sorted("sv", &["ö", "z", "å", "a", "ä"]),
+            vec!["a", "z", "å", "ä", "ö"]
+        );
+        // en allemand "ö" reste à côté de "o"
+        assert_eq!(sorted("de", &["z", "ö", "o"]), vec!["o", "ö", "z"]);
+    }
+
+    #[test]
+    fn unknown_locale_is_rejected() {
+        assert!(LocaleSettings::from_name("xx-nowhere").is_err());
+        assert!(LocaleSettings::from_name("fr").is_ok());
+    }
+}
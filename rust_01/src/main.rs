@@ -1,8 +1,31 @@
-use clap::Parser;
-use num_format::{Locale, ToFormattedString};
+use clap::{Parser, Subcommand};
+use num_format::ToFormattedString;
 use regex::Regex;
-use std::collections::HashMap;
-use std::io::{self, Read};
+use std::fs;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, Instant};
+use wordfreq::approx::{self, SpaceSaving};
+use wordfreq::code::{CodeLang, CodeMode};
+use wordfreq::collocation::{CollocationFinder, Measure};
+use wordfreq::decode::{self, Encoding};
+use wordfreq::extract::{self, InputFormat};
+use wordfreq::fields::{self, Delimiter};
+use wordfreq::follow::{self, LiveCounter, Retention};
+use wordfreq::kwic::{self, ContextUnit};
+use wordfreq::language::{self, Language};
+use wordfreq::matrix::{Matrix, Pruning};
+use wordfreq::server;
+use wordfreq::snapshot::Vocabulary;
+use wordfreq::spell::{self, Dictionary};
+use wordfreq::timeline::{self, Timeline};
+use wordfreq::tokenizer::Numbers;
+use wordfreq::variants::{self, GroupBy};
+use wordfreq::wordcloud;
+use wordfreq::{LocaleSettings, Tokenizer, Unit, WordCounter, snapshot};
+use wordfreq::{position, readability};
 
 /// Count word frequency in text
 #[derive(Parser)]
@@ -10,77 +33,1122 @@ use std::io::{self, Read};
     name = "wordfreq",
     about = "Count word frequency in text",
     author,
-    version
+    version,
+    args_conflicts_with_subcommands = true
 )]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Text to analyze (or use stdin)
     #[arg(value_name = "TEXT")]
     text: Option<String>,
 
+    /// Read input from a file instead of stdin (repeatable)
+    #[arg(short, long, value_name = "PATH")]
+    file: Vec<PathBuf>,
+
     /// Show top N words
-    #[arg(long)]
+    #[arg(long, global = true)]
     top: Option<usize>,
 
     /// Case insensitive counting
     #[arg(long)]
     ignore_case: bool,
 
+    /// What to count: word, char, grapheme or byte (raw input bytes)
+    #[arg(long, default_value = "word")]
+    unit: Unit,
+
     /// Minimum word length to count
     #[arg(long)]
     min_length: Option<usize>,
+
+    /// Count source-code identifiers, skipping language keywords: split (camelCase and
+    /// snake_case sub-words, the default), whole identifiers, or both
+    #[arg(long, value_name = "MODE", num_args = 0..=1, default_missing_value = "split")]
+    code: Option<CodeMode>,
+
+    /// Language whose keywords --code skips: rust, python, javascript, go, c, c++ or
+    /// java (default: from each file's extension; no keyword is skipped if unknown)
+    #[arg(long, value_name = "LANG", requires = "code")]
+    code_lang: Option<CodeLang>,
+
+    /// Only count words matching REGEX (after case folding)
+    #[arg(long = "match", value_name = "REGEX")]
+    match_re: Option<String>,
+
+    /// Do not count words matching REGEX (after case folding)
+    #[arg(long, value_name = "REGEX")]
+    exclude: Option<String>,
+
+    /// Do not count tokens made only of digits
+    #[arg(long, conflicts_with = "mask_numbers")]
+    no_numbers: bool,
+
+    /// Count numbers, hex IDs and UUIDs as <NUM>, <HEX> and <UUID>
+    #[arg(long)]
+    mask_numbers: bool,
+
+    /// Locale for number formatting and alphabetical tie-breaks (en, fr, de, sv...)
+    #[arg(long, global = true, default_value = "en")]
+    locale: String,
+
+    /// Input encoding: auto (BOM, else UTF-8), utf-8, latin1, windows-1252, utf-16, utf-16le, utf-16be
+    #[arg(long, default_value = "auto")]
+    encoding: Encoding,
+
+    /// Replace invalid input bytes instead of failing (reports how many were replaced)
+    #[arg(long)]
+    lossy: bool,
+
+    /// Input format: text, html (strip tags/scripts/entities), markdown (drop code, URLs,
+    /// front matter) or code-comments (only comments and string literals)
+    #[arg(long, default_value = "text")]
+    input_format: InputFormat,
+
+    /// Ignore the stopwords of LANG (en, fr, de...), or of each input's detected
+    /// language with "auto"
+    #[arg(long, value_name = "LANG")]
+    stopwords: Option<String>,
+
+    /// Print the most likely languages of each input with a confidence score
+    #[arg(long)]
+    detect_language: bool,
+
+    /// Count the distinct values of column N (from 1) instead of words; empty values are skipped
+    #[arg(long, value_name = "N")]
+    field: Option<usize>,
+
+    /// Field separator for --field (a character, \t, tab or space); default: runs of whitespace
+    #[arg(long, value_name = "CHAR", requires = "field")]
+    delimiter: Option<String>,
+
+    /// Read --field input as CSV with quoted fields (comma-separated unless --delimiter)
+    #[arg(long, requires = "field")]
+    csv: bool,
+
+    /// Print every occurrence of WORD with its context instead of counting
+    #[arg(long, value_name = "WORD")]
+    kwic: Option<String>,
+
+    /// Amount of left/right context for --kwic
+    #[arg(long, value_name = "N", default_value_t = 5)]
+    context: usize,
+
+    /// Unit of --context: tokens or chars
+    #[arg(long, default_value = "tokens")]
+    context_unit: ContextUnit,
+
+    /// Print the file, line, column and byte offset of every occurrence of WORD
+    #[arg(long = "where", value_name = "WORD")]
+    where_word: Option<String>,
+
+    /// Export the position of every counted word to a TSV file
+    #[arg(long, value_name = "FILE")]
+    positions: Option<PathBuf>,
+
+    /// Export a documents × terms count matrix: dense CSV, or sparse Matrix Market
+    /// when FILE ends in .mtx (with FILE.docs and FILE.terms listing rows and columns)
+    #[arg(long, value_name = "FILE")]
+    matrix: Option<PathBuf>,
+
+    /// Only keep terms found in at least N documents in --matrix
+    #[arg(long, value_name = "N", default_value_t = 1)]
+    min_df: usize,
+
+    /// Only keep the N most frequent terms in --matrix
+    #[arg(long, value_name = "N")]
+    max_features: Option<usize>,
+
+    /// Count the top words (--top, default 10) per time bucket of DURATION (30s, 15m,
+    /// 1h, 1d), using the timestamp of each line
+    #[arg(long, value_name = "DURATION")]
+    bucket: Option<String>,
+
+    /// Where to find the timestamp of each line (first capture group if any);
+    /// default: ISO 8601 and Apache log timestamps
+    #[arg(long, value_name = "REGEX", requires = "bucket")]
+    timestamp_regex: Option<String>,
+
+    /// Print the --bucket table as CSV
+    #[arg(long, requires = "bucket")]
+    bucket_csv: bool,
+
+    /// Flag terms whose count in a bucket is at least FACTOR times the previous
+    /// bucket's (and at least 3)
+    #[arg(long, value_name = "FACTOR", num_args = 0..=1, default_missing_value = "3", requires = "bucket")]
+    spikes: Option<f64>,
+
+    /// Report word pairs that co-occur more often than chance
+    #[arg(long)]
+    collocations: bool,
+
+    /// Co-occurrence window for --collocations (2 = adjacent words)
+    #[arg(long, value_name = "N", default_value_t = 2)]
+    window: usize,
+
+    /// Association measure for --collocations: pmi, t-score or llr
+    #[arg(long, default_value = "pmi")]
+    measure: Measure,
+
+    /// Minimum pair frequency for --collocations
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    min_freq: usize,
+
+    /// Print sentence-level readability metrics (English formulas) instead of counting
+    #[arg(long)]
+    readability: bool,
+
+    /// Keep reading the file (or stdin) as it grows and redraw the top words
+    #[arg(long)]
+    follow: bool,
+
+    /// Seconds between two redraws in --follow mode
+    #[arg(long, value_name = "SECS", default_value_t = 2)]
+    interval: u64,
+
+    /// In --follow mode, only count words seen during the last N minutes
+    #[arg(long, value_name = "MINUTES", conflicts_with = "half_life")]
+    last: Option<u64>,
+
+    /// In --follow mode, halve the weight of past words every N minutes
+    #[arg(long, value_name = "MINUTES")]
+    half_life: Option<f64>,
+
+    /// Count the top words approximately in fixed memory (Space-Saving), streaming
+    /// the input line by line as UTF-8; counts are shown with their error bound
+    #[arg(long)]
+    approx: bool,
+
+    /// Memory budget for --approx (e.g. 512K, 64M, 1G); each tracked word costs its
+    /// length plus a fixed overhead, and words longer than 256 bytes are truncated
+    #[arg(long, value_name = "SIZE", default_value = "16M")]
+    memory: String,
+
+    /// Report counted words missing from a word list (one word per line), with
+    /// near-match suggestions, instead of the frequency report
+    #[arg(long, value_name = "FILE", global = true)]
+    dictionary: Option<PathBuf>,
+
+    /// Maximum edit distance of --dictionary suggestions
+    #[arg(long, value_name = "N", default_value_t = 2, global = true)]
+    max_distance: usize,
+
+    /// Merge spelling variants into groups with their member breakdown instead of the
+    /// frequency report: edit:N (typos, N = 1 to 3), soundex or metaphone (sound-alikes)
+    #[arg(long, value_name = "METHOD", global = true)]
+    group_variants: Option<GroupBy>,
+
+    /// Also draw the top words (--top, default 100) as an SVG word cloud
+    #[arg(long, value_name = "FILE", global = true)]
+    wordcloud: Option<PathBuf>,
+
+    /// Word cloud colors: category10, viridis, warm, grey, or a comma-separated
+    /// list such as "#336699,#cc3300"
+    #[arg(long, default_value = "category10", global = true)]
+    palette: String,
+
+    /// Random seed of the word cloud layout and colors (same seed, same SVG)
+    #[arg(long, default_value_t = 42, global = true)]
+    seed: u64,
+
+    /// Add the counts of a snapshot file before counting (repeatable)
+    #[arg(long, value_name = "FILE")]
+    load: Vec<PathBuf>,
+
+    /// Save the resulting counts to a snapshot file
+    #[arg(long, value_name = "FILE", global = true)]
+    save: Option<PathBuf>,
 }
 
-fn read_stdin() -> io::Result<String> {
-    let mut buf = String::new();
-    io::stdin().read_to_string(&mut buf)?;
+#[derive(Subcommand)]
+enum Command {
+    /// Combine several snapshot files and print the report
+    Merge {
+        /// Snapshot files to merge
+        #[arg(value_name = "FILE", required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Run a local HTTP API: POST /count, POST /corpus/{name},
+    /// GET /corpus/{name}/top, DELETE /corpus/{name} (JSON)
+    Serve {
+        /// Address to listen on
+        #[arg(long, value_name = "ADDR", default_value = "127.0.0.1:8080")]
+        bind: String,
+    },
+}
+
+fn read_stdin() -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    io::stdin().read_to_end(&mut buf)?;
     Ok(buf)
 }
 
+/// Décode l'entrée brute et signale les octets remplacés en mode lossy
+fn decode_input(bytes: &[u8], args: &Args) -> Result<String, String> {
+    let decoded = decode::decode(bytes, args.encoding, args.lossy).map_err(|e| e.to_string())?;
+    if decoded.replaced_bytes > 0 {
+        eprintln!(
+            "wordfreq: replaced {} invalid byte(s) while decoding {}",
+            decoded.replaced_bytes,
+            decoded.encoding.name()
+        );
+    }
+    Ok(decoded.text)
+}
+
+/// Un texte d'entrée décodé et extrait, avec son nom pour les rapports
+struct Document {
+    name: String,
+    text: String,
+    /// mots-outils ignorés pour ce document (--stopwords)
+    stopwords: Option<&'static Language>,
+    /// langage dont --code ignore les mots-clés
+    code_lang: Option<CodeLang>,
+}
+
+impl Document {
+    /// Le tokenizer commun, avec les mots-outils et les mots-clés propres au
+    /// document
+    fn tokenizer(&self, base: &Tokenizer) -> Tokenizer {
+        base.clone()
+            .stopwords(self.stopwords)
+            .code_lang(self.code_lang)
+    }
+}
+
+/// Valeur de --stopwords
+enum Stopwords {
+    None,
+    Fixed(&'static Language),
+    /// langue détectée document par document
+    Auto,
+}
+
+fn parse_stopwords(arg: Option<&str>) -> Result<Stopwords, String> {
+    match arg {
+        None => Ok(Stopwords::None),
+        Some("auto") => Ok(Stopwords::Auto),
+        Some(code) => language::by_code(code)
+            .map(Stopwords::Fixed)
+            .ok_or_else(|| {
+                let known: Vec<&str> = language::LANGUAGES.iter().map(|l| l.code).collect();
+                format!(
+                    "invalid --stopwords: unknown language '{code}' (expected auto or one of {})",
+                    known.join(", ")
+                )
+            }),
+    }
+}
+
+/// Octets bruts de TEXT, puis de chaque --file ; stdin si aucun des deux
+/// n'est donné
+fn read_sources(args: &Args) -> Result<Vec<(String, Vec<u8>)>, String> {
+    let mut sources = Vec::new();
+
+    if let Some(t) = &args.text {
+        sources.push(("<text>".to_string(), t.as_bytes().to_vec()));
+    }
+
+    for path in &args.file {
+        let bytes = fs::read(path).map_err(|e| format!("cannot read '{}': {e}", path.display()))?;
+        sources.push((path.display().to_string(), bytes));
+    }
+
+    if sources.is_empty() {
+        let bytes = read_stdin().map_err(|e| format!("failed to read from stdin: {e}"))?;
+        sources.push(("<stdin>".to_string(), bytes));
+    }
+
+    Ok(sources)
+}
+
+/// Les sources décodées puis passées à l'extraction de `--input-format`
+fn read_documents(args: &Args) -> Result<Vec<Document>, String> {
+    let stopwords = parse_stopwords(args.stopwords.as_deref())?;
+    let mut docs = Vec::new();
+
+    for (name, bytes) in read_sources(args)? {
+        let text = if name == "<text>" {
+            // TEXT est déjà une chaîne UTF-8 valide
+            String::from_utf8(bytes).unwrap_or_default()
+        } else if name == "<stdin>" {
+            decode_input(&bytes, args)?
+        } else {
+            decode_input(&bytes, args).map_err(|e| format!("{name}: {e}"))?
+        };
+        let text = extract::extract(&text, args.input_format);
+        let stopwords = match stopwords {
+            Stopwords::None => None,
+            Stopwords::Fixed(lang) => Some(lang),
+            // texte trop court : aucun mot-outil ignoré
+            Stopwords::Auto => language::detect(&text).first().map(|g| g.language),
+        };
+        let code_lang = args.code_lang.or_else(|| CodeLang::from_path(&name));
+        docs.push(Document {
+            name,
+            text,
+            stopwords,
+            code_lang,
+        });
+    }
+
+    Ok(docs)
+}
+
 fn main() {
     let args = Args::parse();
 
-    let mut input = match args.text {
-        Some(t) => t,
-        None => read_stdin().expect("Failed to read from stdin"),
+    if let Err(e) = run(args) {
+        eprintln!("wordfreq: {e}");
+        std::process::exit(1);
+    }
+}
+
+fn run(args: Args) -> Result<(), String> {
+    let locale =
+        LocaleSettings::from_name(&args.locale).map_err(|e| format!("invalid --locale: {e}"))?;
+
+    let counter = match &args.command {
+        Some(Command::Serve { bind }) => {
+            let listener =
+                TcpListener::bind(bind).map_err(|e| format!("cannot listen on {bind}: {e}"))?;
+            eprintln!("wordfreq: listening on http://{bind}");
+            return server::serve(listener).map_err(|e| e.to_string());
+        }
+        Some(Command::Merge { files }) => {
+            // le premier snapshot fixe le vocabulaire des suivants
+            let first = snapshot::load(&files[0])
+                .map_err(|e| format!("cannot load snapshot '{}': {e}", files[0].display()))?;
+            load_snapshots(&files[1..], first)?
+        }
+        None => {
+            let numbers = if args.mask_numbers {
+                Numbers::Mask
+            } else if args.no_numbers {
+                Numbers::Drop
+            } else {
+                Numbers::Keep
+            };
+            let tokenizer = Tokenizer::new()
+                .unit(args.unit)
+                .ignore_case(args.ignore_case)
+                .min_length(args.min_length)
+                .numbers(numbers)
+                .code(args.code)
+                .code_lang(
+                    // modes sans documents (--follow, --approx) : le premier fichier
+                    args.code_lang.or_else(|| {
+                        args.file
+                            .first()
+                            .and_then(|p| CodeLang::from_path(&p.to_string_lossy()))
+                    }),
+                )
+                .matching(compile_regex("--match", args.match_re.as_deref())?)
+                .excluding(compile_regex("--exclude", args.exclude.as_deref())?);
+
+            if args.unit == Unit::Byte {
+                if args.follow
+                    || args.approx
+                    || args.detect_language
+                    || args.kwic.is_some()
+                    || args.readability
+                    || args.where_word.is_some()
+                    || args.positions.is_some()
+                    || args.bucket.is_some()
+                    || args.collocations
+                    || args.field.is_some()
+                    || args.matrix.is_some()
+                {
+                    return Err("--unit byte only supports the frequency report".into());
+                }
+                if args.stopwords.is_some()
+                    || args.code.is_some()
+                    || args.match_re.is_some()
+                    || args.exclude.is_some()
+                    || args.min_length.is_some()
+                    || args.no_numbers
+                    || args.mask_numbers
+                    || args.input_format != InputFormat::Text
+                {
+                    return Err("--unit byte counts raw bytes: word filters and --input-format do not apply".into());
+                }
+                // octets bruts : ni décodage ni extraction
+                let mut counter =
+                    load_snapshots(&args.load, WordCounter::with_tokenizer(tokenizer))?;
+                for (_, bytes) in read_sources(&args)? {
+                    counter.feed_bytes(&bytes);
+                }
+                return finish(counter, &args, &locale);
+            }
+
+            if args.follow {
+                let tokenizer = fixed_stopwords(tokenizer, &args, "--follow")?;
+                return follow_mode(tokenizer, &args, &locale);
+            }
+
+            if args.approx {
+                let tokenizer = fixed_stopwords(tokenizer, &args, "--approx")?;
+                return approx_mode(&tokenizer, &args, &locale);
+            }
+
+            let docs = read_documents(&args)?;
+
+            if args.detect_language {
+                print_languages(&docs);
+                return Ok(());
+            }
+
+            if let Some(word) = &args.kwic {
+                print_kwic(&docs, &tokenizer, word, &args);
+                return Ok(());
+            }
+
+            if args.readability {
+                print_readability(&docs, &locale);
+                return Ok(());
+            }
+
+            if let Some(word) = &args.where_word {
+                print_where(&docs, &tokenizer, word);
+                return Ok(());
+            }
+
+            if let Some(path) = &args.positions {
+                write_positions(path, &docs, &tokenizer)
+                    .map_err(|e| format!("cannot write positions '{}': {e}", path.display()))?;
+            }
+
+            if let Some(bucket) = &args.bucket {
+                let bucket = timeline::parse_duration(bucket)
+                    .map_err(|e| format!("invalid --bucket: {e}"))?;
+                let pattern = args
+                    .timestamp_regex
+                    .as_deref()
+                    .unwrap_or(timeline::DEFAULT_TIMESTAMP);
+                let re =
+                    Regex::new(pattern).map_err(|e| format!("invalid --timestamp-regex: {e}"))?;
+                let mut tl = Timeline::new(bucket, re);
+                for doc in &docs {
+                    tl.feed(&doc.tokenizer(&tokenizer), &doc.text);
+                }
+                return print_timeline(&tl, &args, &locale);
+            }
+
+            if args.collocations {
+                print_collocations(&docs, &tokenizer, &args, &locale);
+                return Ok(());
+            }
+
+            let mut counter =
+                load_snapshots(&args.load, WordCounter::with_tokenizer(tokenizer.clone()))?;
+            let delimiter = field_delimiter(&args)?;
+            let mut per_doc = Vec::new();
+            for doc in &docs {
+                let tokenizer = doc.tokenizer(&tokenizer);
+                let mut doc_counter = WordCounter::new();
+                if let Some(field) = args.field {
+                    // chaque valeur est un seul "mot", filtré et normalisé
+                    // comme les autres (casse, --match, --exclude...)
+                    for value in fields::values(&doc.text, field, delimiter) {
+                        if let Some(v) = tokenizer.normalize(&value) {
+                            doc_counter.add(&v, 1);
+                        }
+                    }
+                } else {
+                    doc_counter.feed_with(&tokenizer, &doc.text);
+                }
+                if args.matrix.is_some() {
+                    per_doc.push((doc.name.clone(), doc_counter.clone()));
+                }
+                counter.merge(doc_counter);
+            }
+
+            if let Some(path) = &args.matrix {
+                let pruning = Pruning {
+                    min_df: args.min_df,
+                    max_features: args.max_features,
+                };
+                write_matrix(path, &Matrix::build(&per_doc, pruning))
+                    .map_err(|e| format!("cannot write matrix '{}': {e}", path.display()))?;
+            }
+            counter
+        }
+    };
+
+    finish(counter, &args, &locale)
+}
+
+fn field_delimiter(args: &Args) -> Result<Delimiter, String> {
+    if args.field == Some(0) {
+        return Err("invalid --field: columns are numbered from 1".into());
+    }
+    let delimiter = args
+        .delimiter
+        .as_deref()
+        .map(fields::parse_delimiter)
+        .transpose()
+        .map_err(|e| format!("invalid --delimiter: {e}"))?;
+    Ok(match (args.csv, delimiter) {
+        (true, d) => Delimiter::Csv(d.unwrap_or(',')),
+        (false, Some(d)) => Delimiter::Char(d),
+        (false, None) => Delimiter::Whitespace,
+    })
+}
+
+fn compile_regex(flag: &str, pattern: Option<&str>) -> Result<Option<Regex>, String> {
+    pattern
+        .map(|p| Regex::new(p).map_err(|e| format!("invalid {flag}: {e}")))
+        .transpose()
+}
+
+/// Les modes en flux n'ont pas de documents : seule une langue fixe est
+/// possible pour --stopwords
+fn fixed_stopwords(tokenizer: Tokenizer, args: &Args, mode: &str) -> Result<Tokenizer, String> {
+    match parse_stopwords(args.stopwords.as_deref())? {
+        Stopwords::None => Ok(tokenizer),
+        Stopwords::Fixed(lang) => Ok(tokenizer.stopwords(Some(lang))),
+        Stopwords::Auto => Err(format!("--stopwords auto is not supported with {mode}")),
+    }
+}
+
+/// `--approx` : lit les entrées par lignes (ou morceaux de 1 Mio) sans
+/// jamais les garder en mémoire, et compte avec un Space-Saving de taille fixe
+fn approx_mode(tokenizer: &Tokenizer, args: &Args, locale: &LocaleSettings) -> Result<(), String> {
+    if !matches!(args.encoding, Encoding::Auto | Encoding::Utf8)
+        || args.input_format != InputFormat::Text
+        || !args.load.is_empty()
+        || args.save.is_some()
+    {
+        return Err(
+            "--approx only supports UTF-8 text input and cannot load or save snapshots".into(),
+        );
+    }
+    let budget = approx::parse_size(&args.memory).map_err(|e| format!("invalid --memory: {e}"))?;
+    let mut sketch = SpaceSaving::with_memory(budget);
+
+    let mut feed_lines = |name: &str, reader: &mut dyn BufRead| -> Result<(), String> {
+        sketch
+            .feed_reader(tokenizer, reader)
+            .map_err(|e| format!("error while reading '{name}': {e}"))
+    };
+
+    if let Some(t) = &args.text {
+        feed_lines("<text>", &mut t.as_bytes())?;
+    }
+    for path in &args.file {
+        let name = path.display().to_string();
+        let file = fs::File::open(path).map_err(|e| format!("cannot read '{name}': {e}"))?;
+        feed_lines(&name, &mut BufReader::new(file))?;
+    }
+    if args.text.is_none() && args.file.is_empty() {
+        feed_lines("<stdin>", &mut io::stdin().lock())?;
+    }
+
+    let n = args.top.unwrap_or(10);
+    let top = sketch.top(n);
+    let num = |v: usize| v.to_formatted_string(&locale.number);
+    println!(
+        "Top {} {} (approximate: {} counters, {} total, max error {}):\n",
+        n,
+        args.unit.plural(),
+        num(sketch.len()),
+        num(sketch.total()),
+        num(sketch.max_error())
+    );
+    for e in top {
+        if e.error == 0 {
+            println!("{}: {}", e.word, num(e.count));
+        } else {
+            println!(
+                "{}: {} (±{}, at least {})",
+                e.word,
+                num(e.count),
+                num(e.error),
+                num(e.lower_bound())
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Sauvegarde éventuelle (--save) puis rapport de fréquences
+fn finish(counter: WordCounter, args: &Args, locale: &LocaleSettings) -> Result<(), String> {
+    if let Some(path) = &args.save {
+        snapshot::save(path, &counter)
+            .map_err(|e| format!("cannot save snapshot '{}': {e}", path.display()))?;
+    }
+
+    if let Some(path) = &args.wordcloud {
+        let opts = wordcloud::Options {
+            palette: wordcloud::parse_palette(&args.palette)
+                .map_err(|e| format!("invalid --palette: {e}"))?,
+            seed: args.seed,
+            ..wordcloud::Options::default()
+        };
+        let mut words = counter.ranked(&locale.collator);
+        words.truncate(args.top.unwrap_or(100));
+        let svg = wordcloud::to_svg(&wordcloud::layout(&words, &opts), &opts);
+        fs::write(path, svg)
+            .map_err(|e| format!("cannot write word cloud '{}': {e}", path.display()))?;
+    }
+
+    if let Some(by) = args.group_variants {
+        print_variants(&counter, by, args.top, locale);
+        return Ok(());
+    }
+
+    if let Some(path) = &args.dictionary {
+        let text = fs::read_to_string(path)
+            .map_err(|e| format!("cannot read dictionary '{}': {e}", path.display()))?;
+        print_unknown(&counter, &Dictionary::parse(&text), args, locale);
+        return Ok(());
+    }
+
+    let noun = if args.field.is_some() {
+        ("value", "values")
+    } else {
+        (args.unit.name(), args.unit.plural())
+    };
+    print_report(&counter, args.top, locale, noun);
+    Ok(())
+}
+
+/// Ajoute les snapshots `files` aux compteurs de `counter`
+fn load_snapshots(files: &[PathBuf], mut counter: WordCounter) -> Result<WordCounter, String> {
+    for path in files {
+        let snap = snapshot::load(path)
+            .map_err(|e| format!("cannot load snapshot '{}': {e}", path.display()))?;
+        let (expected, found) = (
+            Vocabulary::of(counter.tokenizer()),
+            Vocabulary::of(snap.tokenizer()),
+        );
+        if found != expected {
+            return Err(format!(
+                "snapshot '{}' was counted with {found}, not {expected}",
+                path.display()
+            ));
+        }
+        counter.merge(snap);
+    }
+    Ok(counter)
+}
+
+/// Boucle `--follow` : accumule les lignes pendant `--interval` puis
+/// réaffiche le classement, jusqu'à la fermeture de stdin (ou Ctrl-C)
+fn follow_mode(tokenizer: Tokenizer, args: &Args, locale: &LocaleSettings) -> Result<(), String> {
+    // une seule source suivie : le reste serait ignoré sans le dire
+    if args.text.is_some() {
+        return Err("--follow reads a file or stdin, not TEXT".into());
+    }
+    if args.file.len() > 1 {
+        return Err("--follow follows a single --file".into());
+    }
+    let retention = match (args.last, args.half_life) {
+        (Some(m), _) => Retention::Window(
+            m.checked_mul(60)
+                .map(Duration::from_secs)
+                .ok_or_else(|| format!("invalid --last: {m} minutes is too long"))?,
+        ),
+        (_, Some(m)) => {
+            let half_life = Duration::try_from_secs_f64(m * 60.0)
+                .ok()
+                .filter(|d| m.is_finite() && !d.is_zero())
+                .ok_or_else(|| {
+                    format!("invalid --half-life: expected a positive number of minutes, got {m}")
+                })?;
+            Retention::Decay { half_life }
+        }
+        _ => Retention::All,
+    };
+    let source = args.file.first().cloned();
+    let name = source
+        .as_ref()
+        .map_or("<stdin>".to_string(), |p| p.display().to_string());
+    let rx = follow::tail(source, Duration::from_millis(250))
+        .map_err(|e| format!("cannot follow '{name}': {e}"))?;
+
+    let interval = Duration::from_secs(args.interval.max(1));
+    if Instant::now().checked_add(interval).is_none() {
+        return Err(format!(
+            "invalid --interval: {} seconds is too long",
+            args.interval
+        ));
+    }
+    let mut live = LiveCounter::new(tokenizer, retention);
+    let started = Instant::now();
+
+    loop {
+        let deadline = Instant::now()
+            .checked_add(interval)
+            .ok_or_else(|| format!("invalid --interval: {} seconds is too long", args.interval))?;
+        let mut batch = String::new();
+        let mut closed = false;
+
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+            match rx.recv_timeout(left) {
+                Ok(Ok(line)) => batch.push_str(&line),
+                Ok(Err(e)) => return Err(format!("error while reading '{name}': {e}")),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => {
+                    closed = true;
+                    break;
+                }
+            }
+        }
+
+        let now = Instant::now();
+        live.feed(&extract::extract(&batch, args.input_format), now);
+
+        // efface l'écran et replace le curseur en haut à gauche
+        print!("\x1b[2J\x1b[H");
+        let n = args.top.unwrap_or(10);
+        let scope = match retention {
+            Retention::All => "all lines".to_string(),
+            Retention::Window(w) => format!("last {} min", w.as_secs() / 60),
+            Retention::Decay { half_life } => {
+                format!("half-life {:.1} min", half_life.as_secs_f64() / 60.0)
+            }
+        };
+        println!(
+            "Following {name} ({scope}, {}s elapsed) - top {n} {}:\n",
+            started.elapsed().as_secs(),
+            args.unit.plural()
+        );
+        for (w, c) in live.top(n) {
+            match retention {
+                Retention::Decay { .. } => println!("{w}: {c:.1}"),
+                _ => println!(
+                    "{w}: {}",
+                    (c.round() as u64).to_formatted_string(&locale.number)
+                ),
+            }
+        }
+        io::stdout().flush().map_err(|e| e.to_string())?;
+
+        if closed {
+            return Ok(());
+        }
+    }
+}
+
+fn print_kwic(docs: &[Document], tokenizer: &Tokenizer, word: &str, args: &Args) {
+    let mut lines = Vec::new();
+    for doc in docs {
+        lines.extend(kwic::concordance(
+            &doc.name,
+            &doc.text,
+            &doc.tokenizer(tokenizer),
+            word,
+            args.context,
+            args.context_unit,
+        ));
+    }
+
+    for line in kwic::format_lines(&lines) {
+        println!("{line}");
+    }
+}
+
+/// Les trois langues les plus probables de chaque document
+fn print_languages(docs: &[Document]) {
+    for doc in docs {
+        let guesses = language::detect(&doc.text);
+        if guesses.is_empty() {
+            println!("{}: unknown (not enough text)", doc.name);
+            continue;
+        }
+        let best: Vec<String> = guesses
+            .iter()
+            .take(3)
+            .map(|g| {
+                format!(
+                    "{} ({}) {:.0}%",
+                    g.language.name,
+                    g.language.code,
+                    g.confidence * 100.0
+                )
+            })
+            .collect();
+        println!("{}: {}", doc.name, best.join(", "));
+    }
+}
+
+fn print_readability(docs: &[Document], locale: &LocaleSettings) {
+    let text = docs
+        .iter()
+        .map(|d| d.text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    let n = |v: usize| v.to_formatted_string(&locale.number);
+
+    println!("Readability:\n");
+    let Some(r) = readability::analyze(&text) else {
+        println!("Not enough text: no words found");
+        return;
     };
+    println!("Sentences: {}", n(r.sentences));
+    println!("Words: {}", n(r.words));
+    println!("Syllables: {}", n(r.syllables));
+    println!("Complex words (3+ syllables): {}", n(r.complex_words));
+    println!(
+        "Average sentence length: {:.1} words",
+        r.avg_sentence_length
+    );
+    println!("Syllables per word: {:.2}", r.syllables_per_word);
+    println!("Flesch Reading Ease: {:.1}", r.flesch_reading_ease);
+    println!("Flesch-Kincaid grade: {:.1}", r.flesch_kincaid_grade);
+    println!("Gunning Fog index: {:.1}", r.gunning_fog);
+}
+
+fn print_where(docs: &[Document], tokenizer: &Tokenizer, word: &str) {
+    for doc in docs {
+        let tokenizer = doc.tokenizer(tokenizer);
+        let Some(wanted) = tokenizer.normalize(word) else {
+            continue;
+        };
+        for o in position::occurrences(&doc.text, &tokenizer).filter(|o| o.word == wanted) {
+            println!("{}:{}:{}: byte {}", doc.name, o.line, o.column, o.offset);
+        }
+    }
+}
 
-    if args.ignore_case {
-        input = input.to_lowercase();
+/// TSV : file, line, column, offset, word (tabulations et retours à la ligne
+/// des mots entre guillemets échappés en \t et \n)
+fn write_positions(path: &PathBuf, docs: &[Document], tokenizer: &Tokenizer) -> io::Result<()> {
+    let mut out = BufWriter::new(fs::File::create(path)?);
+    writeln!(out, "file\tline\tcolumn\toffset\tword")?;
+
+    for doc in docs {
+        let tokenizer = doc.tokenizer(tokenizer);
+        for o in position::occurrences(&doc.text, &tokenizer) {
+            let word = o
+                .word
+                .replace('\\', "\\\\")
+                .replace('\t', "\\t")
+                .replace('\n', "\\n");
+            writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}",
+                doc.name, o.line, o.column, o.offset, word
+            )?;
+        }
     }
 
-    // Gestion des mots avec guillemets + mots normaux
-    // - "World"  → token = "World"
-    // - 'Hello'  → token = 'Hello'
-    // - hello    → token = hello
-    let re = Regex::new(r#""[^"]+"|'[^']+'|\w+"#).expect("invalid regex");
+    out.flush()
+}
 
-    let mut freq: HashMap<String, usize> = HashMap::new();
-    for m in re.find_iter(&input) {
-        let w = m.as_str().to_string();
+/// CSV, ou Matrix Market si le fichier finit par .mtx ; dans ce cas les noms
+/// des documents et des termes vont dans FILE.docs et FILE.terms, un par ligne
+fn write_matrix(path: &PathBuf, matrix: &Matrix) -> io::Result<()> {
+    let out = BufWriter::new(fs::File::create(path)?);
+    if path.extension().is_none_or(|e| e != "mtx") {
+        return matrix.write_csv(out);
+    }
 
-        // Filtre de longueur minimale
-        if let Some(min) = args.min_length {
-            if w.chars().count() < min {
-                continue;
+    matrix.write_matrix_market(out)?;
+    for (ext, names) in [("docs", &matrix.documents), ("terms", &matrix.terms)] {
+        let mut side = path.as_os_str().to_owned();
+        side.push(format!(".{ext}"));
+        let mut out = BufWriter::new(fs::File::create(side)?);
+        for name in names {
+            // un nom par ligne : les retours à la ligne sont échappés
+            writeln!(out, "{}", name.replace('\\', "\\\\").replace('\n', "\\n"))?;
+        }
+        out.flush()?;
+    }
+    Ok(())
+}
+
+/// Tableau (ou CSV) tranches × termes ; les pics sont marqués d'un `*` dans
+/// le tableau et listés dans une colonne `spikes` en CSV
+fn print_timeline(tl: &Timeline, args: &Args, locale: &LocaleSettings) -> Result<(), String> {
+    if tl.undated_lines > 0 {
+        eprintln!(
+            "wordfreq: skipped {} line(s) before the first timestamp",
+            tl.undated_lines
+        );
+    }
+    let terms = tl.top_terms(args.top.unwrap_or(10));
+    let series = tl.series(&terms)?;
+    let spikes = args
+        .spikes
+        .map(|f| timeline::spikes(&series, f, 3))
+        .unwrap_or_default();
+    let is_spike = |i: usize, j: usize| spikes.contains(&(i, j));
+
+    if args.bucket_csv {
+        let quote = |s: &str| {
+            if s.contains([',', '"', '\n']) {
+                format!("\"{}\"", s.replace('"', "\"\""))
+            } else {
+                s.to_string()
+            }
+        };
+        let header: Vec<String> = terms.iter().map(|t| quote(t)).collect();
+        let extra = if args.spikes.is_some() { ",spikes" } else { "" };
+        println!("bucket,{}{extra}", header.join(","));
+        for (i, (t, row)) in series.iter().enumerate() {
+            let cells: Vec<String> = row.iter().map(|c| c.to_string()).collect();
+            print!("{},{}", timeline::format_time(*t), cells.join(","));
+            if args.spikes.is_some() {
+                let spiking: Vec<&str> = (0..terms.len())
+                    .filter(|&j| is_spike(i, j))
+                    .map(|j| terms[j])
+                    .collect();
+                print!(",{}", quote(&spiking.join(" ")));
             }
+            println!();
+        }
+        return Ok(());
+    }
+
+    let cell = |i: usize, j: usize, c: usize| {
+        let mark = if is_spike(i, j) { "*" } else { "" };
+        format!("{}{mark}", c.to_formatted_string(&locale.number))
+    };
+    let widths: Vec<usize> = terms
+        .iter()
+        .enumerate()
+        .map(|(j, t)| {
+            series
+                .iter()
+                .enumerate()
+                .map(|(i, (_, row))| cell(i, j, row[j]).chars().count())
+                .chain([t.chars().count()])
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    let label_width = series
+        .first()
+        .map_or(6, |(t, _)| timeline::format_time(*t).len());
+
+    print!("{:<label_width$}", "bucket");
+    for (t, w) in terms.iter().zip(&widths) {
+        print!("  {t:>w$}");
+    }
+    println!();
+    for (i, (t, row)) in series.iter().enumerate() {
+        print!("{:<label_width$}", timeline::format_time(*t));
+        for (j, (c, w)) in row.iter().zip(&widths).enumerate() {
+            print!("  {:>w$}", cell(i, j, *c));
+        }
+        println!();
+    }
+    if !spikes.is_empty() {
+        println!(
+            "\n* spike: at least {}x the previous bucket",
+            args.spikes.unwrap_or(3.0)
+        );
+    }
+    Ok(())
+}
+
+fn print_collocations(
+    docs: &[Document],
+    tokenizer: &Tokenizer,
+    args: &Args,
+    locale: &LocaleSettings,
+) {
+    let mut finder = CollocationFinder::new(args.window);
+    for doc in docs {
+        finder.feed(&doc.tokenizer(tokenizer), &doc.text);
+    }
+
+    let ranked = finder.ranked(args.measure, args.min_freq);
+    let n = args.top.unwrap_or(ranked.len());
+
+    println!(
+        "Top {} collocations ({}, window {}, min freq {}):\n",
+        n.min(ranked.len()),
+        args.measure.name(),
+        args.window.max(2),
+        args.min_freq
+    );
+    for c in ranked.into_iter().take(n) {
+        println!(
+            "{} {}: {:.3} ({})",
+            c.first,
+            c.second,
+            c.score,
+            c.count.to_formatted_string(&locale.number)
+        );
+    }
+}
+
+fn print_variants(counter: &WordCounter, by: GroupBy, top: Option<usize>, locale: &LocaleSettings) {
+    let clusters = variants::group(counter, by, &locale.collator);
+    let n = top.unwrap_or(clusters.len());
+    let num = |v: usize| v.to_formatted_string(&locale.number);
+
+    println!(
+        "Variant groups ({}, {} groups for {} words):\n",
+        by.name(),
+        num(clusters.len()),
+        num(counter.unique())
+    );
+    for c in clusters.into_iter().take(n) {
+        if c.members.len() == 1 {
+            println!("{}: {}", c.name(), num(c.total));
+        } else {
+            let members: Vec<String> = c
+                .members
+                .iter()
+                .map(|(w, n)| format!("{w} {}", num(*n)))
+                .collect();
+            println!("{}: {} ({})", c.name(), num(c.total), members.join(", "));
         }
+    }
+}
+
+fn print_unknown(counter: &WordCounter, dict: &Dictionary, args: &Args, locale: &LocaleSettings) {
+    let unknown = spell::unknown_words(counter, dict, &locale.collator, args.max_distance);
+    let n = args.top.unwrap_or(unknown.len());
+    let num = |v: usize| v.to_formatted_string(&locale.number);
 
-        *freq.entry(w).or_insert(0) += 1;
+    println!(
+        "Unknown words ({} of {} unique, dictionary of {}):\n",
+        num(unknown.len()),
+        num(counter.unique()),
+        num(dict.len())
+    );
+    for u in unknown.into_iter().take(n) {
+        if u.suggestions.is_empty() {
+            println!("{}: {}", u.word, num(u.count));
+        } else {
+            println!(
+                "{}: {} (did you mean: {})",
+                u.word,
+                num(u.count),
+                u.suggestions.join(", ")
+            );
+        }
     }
+}
 
-    let mut items: Vec<(String, usize)> = freq.into_iter().collect();
+/// `noun` : ce qui est compté, au singulier et au pluriel ("word", "words")
+fn print_report(
+    counter: &WordCounter,
+    top: Option<usize>,
+    locale: &LocaleSettings,
+    noun: (&str, &str),
+) {
     // tri: d'abord par fréquence décroissante, puis par ordre alphabétique
-    items.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    // selon la collation de la locale ("é" à côté de "e")
+    let items = counter.ranked(&locale.collator);
 
-    if let Some(n) = args.top {
-        println!("Top {} words:\n", n);
+    if let Some(n) = top {
+        println!("Top {} {}:\n", n, noun.1);
         for (w, c) in items.into_iter().take(n) {
-            println!("{}: {}", w, c.to_formatted_string(&Locale::en));
+            println!("{}: {}", w, c.to_formatted_string(&locale.number));
         }
     } else {
-        println!("Word frequency:\n");
+        let name = noun.0;
+        println!("{}{} frequency:\n", name[..1].to_uppercase(), &name[1..]);
         for (w, c) in items {
-            println!("{}: {}", w, c.to_formatted_string(&Locale::en));
+            println!("{}: {}", w, c.to_formatted_string(&locale.number));
         }
     }
 }
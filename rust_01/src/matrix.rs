@@ -0,0 +1,195 @@
+//! Matrice documents × termes (`--matrix`), en CSV dense ou au format
+//! creux Matrix Market, avec élagage du vocabulaire.
+
+use crate::counter::WordCounter;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Élagage du vocabulaire avant l'export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pruning {
+    /// nombre minimal de documents contenant le terme
+    pub min_df: usize,
+    /// garde seulement les termes les plus fréquents (tous documents confondus)
+    pub max_features: Option<usize>,
+}
+
+impl Default for Pruning {
+    fn default() -> Self {
+        Self {
+            min_df: 1,
+            max_features: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Matrix {
+    pub documents: Vec<String>,
+    /// colonnes, par ordre alphabétique (octets)
+    pub terms: Vec<String>,
+    /// pour chaque document, (colonne, fréquence) non nuls, colonnes croissantes
+    pub rows: Vec<Vec<(usize, usize)>>,
+}
+
+impl Matrix {
+    /// Construit la matrice à partir d'un compteur par document
+    pub fn build(docs: &[(String, WordCounter)], pruning: Pruning) -> Self {
+        // (fréquence totale, nombre de documents) de chaque terme
+        let mut stats: HashMap<&str, (usize, usize)> = HashMap::new();
+        for (_, counter) in docs {
+            for (w, c) in counter {
+                let s = stats.entry(w).or_insert((0, 0));
+                s.0 += c;
+                s.1 += 1;
+            }
+        }
+
+        let mut kept: Vec<(&str, usize)> = stats
+            .into_iter()
+            .filter(|(_, (_, df))| *df >= pruning.min_df)
+            .map(|(w, (total, _))| (w, total))
+            .collect();
+        if let Some(max) = pruning.max_features {
+            kept.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+            kept.truncate(max);
+        }
+
+        let mut terms: Vec<String> = kept.into_iter().map(|(w, _)| w.to_string()).collect();
+        terms.sort();
+        let column: HashMap<&str, usize> = terms
+            .iter()
+            .enumerate()
+            .map(|(i, t)| (t.as_str(), i))
+            .collect();
+
+        let rows = docs
+            .iter()
+            .map(|(_, counter)| {
+                let mut row: Vec<(usize, usize)> = counter
+                    .iter()
+                    .filter_map(|(w, c)| Some((*column.get(w)?, c)))
+                    .collect();
+                row.sort_unstable();
+                row
+            })
+            .collect();
+
+        Self {
+            documents: docs.iter().map(|(name, _)| name.clone()).collect(),
+            terms,
+            rows,
+        }
+    }
+
+    /// Nombre de cellules non nulles
+    pub fn nnz(&self) -> usize {
+        self.rows.iter().map(Vec::len).sum()
+    }
+
+    /// CSV dense : une ligne d'en-tête `document,terme1,terme2...` puis une
+    /// ligne par document
+    pub fn write_csv(&self, mut out: impl Write) -> io::Result<()> {
+        write!(out, "document")?;
+        for t in &self.terms {
+            write!(out, ",{}", csv_field(t))?;
+        }
+        writeln!(out)?;
+
+        for (name, row) in self.documents.iter().zip(&self.rows) {
+            write!(out, "{}", csv_field(name))?;
+            let mut cells = row.iter().peekable();
+            for col in 0..self.terms.len() {
+                let c = match cells.peek() {
+                    Some(&&(j, c)) if j == col => {
+                        cells.next();
+                        c
+                    }
+                    _ => 0,
+                };
+                write!(out, ",{c}")?;
+            }
+            writeln!(out)?;
+        }
+        out.flush()
+    }
+
+    /// Matrix Market creux (`coordinate integer general`), indices à partir
+    /// de 1 ; les noms des lignes et colonnes vont dans des fichiers à part
+    pub fn write_matrix_market(&self, mut out: impl Write) -> io::Result<()> {
+        writeln!(out, "%%MatrixMarket matrix coordinate integer general")?;
+        writeln!(out, "% rows: documents, columns: terms")?;
+        writeln!(
+            out,
+            "{} {} {}",
+            self.documents.len(),
+            self.terms.len(),
+            self.nnz()
+        )?;
+        for (i, row) in self.rows.iter().enumerate() {
+            for (j, c) in row {
+                writeln!(out, "{} {} {c}", i + 1, j + 1)?;
+            }
+        }
+        out.flush()
+    }
+}
+
+/// Met un champ entre guillemets s'il contient une virgule, un guillemet ou
+/// un retour à la ligne
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn docs() -> Vec<(String, WordCounter)> {
+        ["the cat sat", "the dog, the \"end\"", "cat cat"]
+            .iter()
+            .enumerate()
+            .map(|(i, text)| {
+                let mut c = WordCounter::new();
+                c.feed(text);
+                (format!("doc{i}"), c)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn dense_csv() {
+        let m = Matrix::build(&docs(), Pruning::default());
+        let mut out = Vec::new();
+        m.write_csv(&mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "document,\"\"\"end\"\"\",cat,dog,sat,the\n\
+             doc0,0,1,0,1,1\n\
+             doc1,1,0,1,0,2\n\
+             doc2,0,2,0,0,0\n"
+        );
+    }
+
+    #[test]
+    fn pruning_and_matrix_market() {
+        let pruning = Pruning {
+            min_df: 2,
+            max_features: Some(1),
+        };
+        let m = Matrix::build(&docs(), pruning);
+        // "cat" et "the" : 3 occurrences chacun, départagés par ordre alphabétique
+        assert_eq!(m.terms, vec!["cat"]);
+
+        let mut out = Vec::new();
+        m.write_matrix_market(&mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = text.lines().skip(2).collect();
+        assert_eq!(lines, vec!["3 1 2", "1 1 1", "3 1 2"]);
+    }
+}
@@ -0,0 +1,82 @@
+use crate::tokenizer::Tokenizer;
+
+/// Convertit un offset en octets en (ligne, colonne), toutes deux à partir de 1.
+/// La colonne est comptée en caractères, pas en octets.
+#[derive(Debug, Clone)]
+pub struct LineIndex<'a> {
+    text: &'a str,
+    /// offset du début de chaque ligne
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub fn new(text: &'a str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        Self { text, starts }
+    }
+
+    pub fn locate(&self, offset: usize) -> (usize, usize) {
+        let line = self.starts.partition_point(|&s| s <= offset) - 1;
+        let col = self.text[self.starts[line]..offset].chars().count() + 1;
+        (line + 1, col)
+    }
+}
+
+/// Une occurrence d'un mot compté
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Occurrence {
+    /// mot normalisé, tel qu'il apparaît dans les compteurs
+    pub word: String,
+    pub line: usize,
+    pub column: usize,
+    /// offset en octets du début du mot dans le texte
+    pub offset: usize,
+}
+
+/// Toutes les occurrences des mots comptés dans `text`, dans l'ordre
+pub fn occurrences<'a>(
+    text: &'a str,
+    tokenizer: &'a Tokenizer,
+) -> impl Iterator<Item = Occurrence> + 'a {
+    let lines = LineIndex::new(text);
+    tokenizer.tokens(text).filter_map(move |t| {
+        let word = tokenizer.normalize(t.text)?;
+        let (line, column) = lines.locate(t.start);
+        Some(Occurrence {
+            word: word.into_owned(),
+            line,
+            column,
+            offset: t.start,
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locate_lines_and_columns() {
+        let text = "abc\nde été\n\nx";
+        let idx = LineIndex::new(text);
+
+        assert_eq!(idx.locate(0), (1, 1));
+        assert_eq!(idx.locate(2), (1, 3));
+        assert_eq!(idx.locate(4), (2, 1));
+        // le "t" de "été" : colonne en caractères, pas en octets
+        assert_eq!(idx.locate(text.find('t').unwrap()), (2, 5));
+        assert_eq!(idx.locate(text.len() - 1), (4, 1));
+    }
+
+    #[test]
+    fn occurrences_use_normalized_words() {
+        let tok = Tokenizer::new().ignore_case(true).min_length(Some(2));
+        let occ: Vec<Occurrence> = occurrences("a Foo\n  foo", &tok).collect();
+
+        assert_eq!(occ.len(), 2);
+        assert_eq!(occ[0].word, "foo");
+        assert_eq!((occ[0].line, occ[0].column, occ[0].offset), (1, 3, 2));
+        assert_eq!((occ[1].line, occ[1].column, occ[1].offset), (2, 3, 8));
+    }
+}
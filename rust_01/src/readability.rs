@@ -0,0 +1,254 @@
+//! Découpage en phrases et indices de lisibilité (`--readability`).
+//!
+//! Les formules sont celles de l'anglais ; le comptage des syllabes est
+//! heuristique (groupes de voyelles, "e" muet final, etc.) et peut se
+//! tromper d'une syllabe sur certains mots.
+
+use std::ops::Range;
+
+/// Abréviations courantes dont le point ne termine pas la phrase
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "etc", "e.g", "i.e", "inc", "ltd",
+    "co", "fig", "no", "vol", "approx", "dept", "est", "jan", "feb", "mar", "apr", "jun", "jul",
+    "aug", "sep", "sept", "oct", "nov", "dec",
+];
+
+/// Découpe le texte en phrases (plages d'octets, sans les blancs autour)
+pub fn sentences(text: &str) -> Vec<Range<usize>> {
+    let mut out = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        let end_of_sentence = match c {
+            '!' | '?' | '…' => true,
+            '.' => !is_abbreviation(&text[start..i]) && !is_decimal_point(text, i),
+            // une ligne vide termine aussi la phrase (titres, listes)
+            '\n' => matches!(chars.peek(), Some((_, '\n'))),
+            _ => false,
+        };
+        if !end_of_sentence {
+            continue;
+        }
+
+        // ponctuation répétée et guillemets/parenthèses fermants : "Wait?!" »
+        let mut end = i + c.len_utf8();
+        while let Some(&(j, n)) = chars.peek() {
+            if matches!(n, '.' | '!' | '?' | '"' | '\'' | ')' | '»' | '”' | '’') {
+                end = j + n.len_utf8();
+                chars.next();
+            } else {
+                break;
+            }
+        }
+
+        push_trimmed(&mut out, text, start..end);
+        start = end;
+    }
+
+    push_trimmed(&mut out, text, start..text.len());
+    out
+}
+
+fn push_trimmed(out: &mut Vec<Range<usize>>, text: &str, r: Range<usize>) {
+    let s = &text[r.clone()];
+    let lead = s.len() - s.trim_start().len();
+    let trail = s.len() - s.trim_end().len();
+    // une "phrase" sans lettre ni chiffre (ponctuation seule) est ignorée
+    if s.chars().any(char::is_alphanumeric) {
+        out.push(r.start + lead..r.end - trail);
+    }
+}
+
+fn is_abbreviation(before: &str) -> bool {
+    let word: String = before
+        .chars()
+        .rev()
+        .take_while(|c| c.is_alphabetic() || *c == '.')
+        .collect::<Vec<_>>()
+        .into_iter()
+        .rev()
+        .collect();
+    // initiale isolée : "J. Smith" (mais pas "I." ni "A." en fin de phrase)
+    let initial = word.chars().count() == 1
+        && word
+            .chars()
+            .all(|c| c.is_uppercase() && c != 'I' && c != 'A');
+    initial || ABBREVIATIONS.contains(&word.to_lowercase().as_str())
+}
+
+fn is_decimal_point(text: &str, i: usize) -> bool {
+    let before = text[..i].chars().next_back();
+    let after = text[i + 1..].chars().next();
+    matches!((before, after), (Some(b), Some(a)) if b.is_ascii_digit() && a.is_ascii_digit())
+}
+
+/// Nombre de syllabes (heuristique pour l'anglais), au moins 1
+pub fn syllables(word: &str) -> usize {
+    let w: Vec<char> = word
+        .chars()
+        .filter(|c| c.is_alphabetic())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if w.is_empty() {
+        return 0;
+    }
+    if w.len() <= 3 {
+        return 1;
+    }
+
+    let is_vowel = |c: char| matches!(c, 'a' | 'e' | 'i' | 'o' | 'u' | 'y');
+    let mut count = 0;
+    let mut prev_vowel = false;
+    for &c in &w {
+        let v = is_vowel(c);
+        if v && !prev_vowel {
+            count += 1;
+        }
+        prev_vowel = v;
+    }
+
+    let n = w.len();
+    let ends_with = |s: &str| w.iter().rev().take(s.len()).rev().copied().eq(s.chars());
+
+    // "e" muet final (make, those), mais pas "-le" après consonne (table)
+    let silent_e = w[n - 1] == 'e' && !is_vowel(w[n - 2]);
+    let consonant_le = w[n - 2] == 'l' && !is_vowel(w[n - 3]);
+    if silent_e && !consonant_le {
+        count -= 1;
+    }
+    // "-es" / "-ed" muets (makes, jumped), sauf après t/d/s/z/x/ch/sh (wanted, boxes)
+    if (ends_with("es") || ends_with("ed")) && n > 3 {
+        let c = w[n - 3];
+        let sounded = matches!(c, 't' | 'd' | 's' | 'z' | 'x' | 'c' | 'g')
+            || (c == 'h' && matches!(w[n - 4], 'c' | 's'));
+        if !sounded && !is_vowel(c) {
+            count -= 1;
+        }
+    }
+    // hiatus fréquents comptés comme deux syllabes : "ia" (media), "io" (radio),
+    // sauf dans les suffixes "-tion" / "-sion"
+    for i in 1..n {
+        let hiatus = matches!((w[i - 1], w[i]), ('i', 'a') | ('i', 'o') | ('e', 'o'));
+        let suffix = w[i] == 'o' && i >= 2 && matches!(w[i - 2], 't' | 's');
+        if hiatus && !suffix {
+            count += 1;
+        }
+    }
+
+    count.max(1)
+}
+
+/// Résultat du rapport `--readability`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Readability {
+    pub sentences: usize,
+    pub words: usize,
+    pub syllables: usize,
+    /// mots de 3 syllabes ou plus
+    pub complex_words: usize,
+    pub avg_sentence_length: f64,
+    pub syllables_per_word: f64,
+    pub flesch_reading_ease: f64,
+    pub flesch_kincaid_grade: f64,
+    pub gunning_fog: f64,
+}
+
+/// Calcule les indices sur un texte. Les mots sont les suites de lettres
+/// (les nombres et la ponctuation ne comptent pas). `None` sans aucun mot :
+/// les formules donneraient des scores sans signification.
+pub fn analyze(text: &str) -> Option<Readability> {
+    let mut words = 0;
+    let mut syllable_count = 0;
+    let mut complex = 0;
+    for w in text
+        .split(|c: char| !(c.is_alphabetic() || c == '\'' || c == '’'))
+        .filter(|w| w.chars().any(char::is_alphabetic))
+    {
+        let s = syllables(w);
+        words += 1;
+        syllable_count += s;
+        if s >= 3 {
+            complex += 1;
+        }
+    }
+    if words == 0 {
+        return None;
+    }
+
+    // des mots sans ponctuation finale forment tout de même une phrase
+    let sentence_count = sentences(text).len().max(1);
+    let wps = words as f64 / sentence_count as f64;
+    let spw = syllable_count as f64 / words as f64;
+    let complex_ratio = complex as f64 / words as f64;
+
+    Some(Readability {
+        sentences: sentence_count,
+        words,
+        syllables: syllable_count,
+        complex_words: complex,
+        avg_sentence_length: wps,
+        syllables_per_word: spw,
+        flesch_reading_ease: 206.835 - 1.015 * wps - 84.6 * spw,
+        flesch_kincaid_grade: 0.39 * wps + 11.8 * spw - 15.59,
+        gunning_fog: 0.4 * (wps + 100.0 * complex_ratio),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sentence_segmentation() {
+        let text = "Dr. Smith paid $3.50 today. Really?! \"Yes.\" Then he left\n\nTitle";
+        let got: Vec<&str> = sentences(text).into_iter().map(|r| &text[r]).collect();
+
+        assert_eq!(
+            got,
+            vec![
+                "Dr. Smith paid $3.50 today.",
+                "Really?!",
+                "\"Yes.\"",
+                "Then he left",
+                "Title"
+            ]
+        );
+    }
+
+    #[test]
+    fn syllable_heuristic() {
+        for (word, n) in [
+            ("the", 1),
+            ("cat", 1),
+            ("make", 1),
+            ("table", 2),
+            ("jumped", 1),
+            ("wanted", 2),
+            ("boxes", 2),
+            ("readability", 5),
+            ("media", 3),
+            ("beautiful", 3),
+            ("estimation", 4),
+        ] {
+            assert_eq!(syllables(word), n, "{word}");
+        }
+    }
+
+    #[test]
+    fn simple_text_is_easy() {
+        let r = analyze("The cat sat on the mat. The dog ran.").unwrap();
+
+        assert_eq!(r.sentences, 2);
+        assert_eq!(r.words, 9);
+        assert!(r.flesch_reading_ease > 100.0);
+        assert!(r.flesch_kincaid_grade < 1.0);
+    }
+
+    #[test]
+    fn no_words_no_scores() {
+        assert_eq!(analyze(""), None);
+        assert_eq!(analyze("42 -- 3.14 !"), None);
+        assert_eq!(analyze("untitled").map(|r| r.sentences), Some(1));
+    }
+}
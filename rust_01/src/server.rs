@@ -0,0 +1,532 @@
+//! Mode `wordfreq serve` : petite API HTTP/1.1 locale (JSON) au-dessus du
+//! compteur, sans dépendance serveur : un thread par connexion (au plus
+//! [`MAX_CONNECTIONS`] à la fois), une requête par connexion.
+//!
+//! - `POST /count` : compte le texte du corps et renvoie les mots
+//! - `POST /corpus/{name}` : ajoute le texte au corpus nommé (créé au besoin) ;
+//!   409 si `unit`, `code` ou `ignore_case` diffèrent de ceux du corpus
+//! - `GET /corpus/{name}/top?n=10` : les mots les plus fréquents du corpus
+//! - `DELETE /corpus/{name}` : oublie le corpus
+//!
+//! Le corps est un objet JSON dont les champs reprennent les options de la
+//! ligne de commande (`{"text": "...", "ignore_case": true, "top": 5}`) ;
+//! un corps qui n'est pas du JSON est compté tel quel, sans option.
+
+use crate::code::{CodeLang, CodeMode};
+use crate::counter::WordCounter;
+use crate::extract::{self, InputFormat};
+use crate::language;
+use crate::locale::LocaleSettings;
+use crate::snapshot::Vocabulary;
+use crate::tokenizer::{Numbers, Tokenizer, Unit};
+use regex::Regex;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// Taille maximale d'un corps de requête
+const MAX_BODY: usize = 64 << 20;
+
+/// Connexions traitées en même temps ; au-delà, réponse 503 immédiate
+pub const MAX_CONNECTIONS: usize = 32;
+
+/// Options d'une requête de comptage, mêmes noms que les options de la CLI
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CountRequest {
+    pub text: String,
+    pub top: Option<usize>,
+    pub ignore_case: bool,
+    pub unit: Option<String>,
+    pub min_length: Option<usize>,
+    #[serde(rename = "match")]
+    pub match_re: Option<String>,
+    pub exclude: Option<String>,
+    pub no_numbers: bool,
+    pub mask_numbers: bool,
+    pub code: Option<String>,
+    pub code_lang: Option<String>,
+    /// code de langue ou "auto"
+    pub stopwords: Option<String>,
+    pub input_format: Option<String>,
+    pub locale: Option<String>,
+}
+
+impl CountRequest {
+    fn tokenizer(&self, text: &str) -> Result<Tokenizer, String> {
+        let parse_re = |name: &str, re: &Option<String>| {
+            re.as_deref()
+                .map(|p| Regex::new(p).map_err(|e| format!("invalid {name}: {e}")))
+                .transpose()
+        };
+        let numbers = match (self.no_numbers, self.mask_numbers) {
+            (_, true) => Numbers::Mask,
+            (true, false) => Numbers::Drop,
+            _ => Numbers::Keep,
+        };
+        let stopwords = match self.stopwords.as_deref() {
+            None => None,
+            Some("auto") => language::detect(text).first().map(|g| g.language),
+            Some(code) => Some(
+                language::by_code(code)
+                    .ok_or_else(|| format!("invalid stopwords: unknown language '{code}'"))?,
+            ),
+        };
+
+        Ok(Tokenizer::new()
+            .unit(self.unit.as_deref().map_or(Ok(Unit::Word), str::parse)?)
+            .ignore_case(self.ignore_case)
+            .min_length(self.min_length)
+            .numbers(numbers)
+            .code(
+                self.code
+                    .as_deref()
+                    .map(str::parse::<CodeMode>)
+                    .transpose()?,
+            )
+            .code_lang(
+                self.code_lang
+                    .as_deref()
+                    .map(str::parse::<CodeLang>)
+                    .transpose()?,
+            )
+            .stopwords(stopwords)
+            .matching(parse_re("match", &self.match_re)?)
+            .excluding(parse_re("exclude", &self.exclude)?))
+    }
+
+    /// Le texte extrait selon `input_format` et ses compteurs ; le
+    /// tokenizer du compteur porte le vocabulaire de la requête
+    fn count(&self) -> Result<WordCounter, String> {
+        let format = self
+            .input_format
+            .as_deref()
+            .map_or(Ok(InputFormat::Text), str::parse)?;
+        let text = extract::extract(&self.text, format);
+        let tokenizer = self.tokenizer(&text)?;
+        let mut counter = WordCounter::with_tokenizer(Vocabulary::of(&tokenizer).tokenizer());
+        counter.feed_with(&tokenizer, &text);
+        Ok(counter)
+    }
+
+    fn locale(&self) -> Result<LocaleSettings, String> {
+        LocaleSettings::from_name(self.locale.as_deref().unwrap_or("en"))
+            .map_err(|e| format!("invalid locale: {e}"))
+    }
+}
+
+/// Réponse HTTP : statut et corps JSON
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn ok(body: Value) -> Self {
+        Self { status: 200, body }
+    }
+
+    fn error(status: u16, message: impl Into<String>) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.into() }),
+        }
+    }
+}
+
+/// État partagé entre les connexions : les corpus nommés
+#[derive(Debug, Default)]
+pub struct Server {
+    corpora: Mutex<HashMap<String, WordCounter>>,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Traite une requête déjà lue ; `target` peut contenir une query string
+    pub fn handle(&self, method: &str, target: &str, body: &[u8]) -> Response {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+        match (method, segments.as_slice()) {
+            ("POST", ["count"]) => self.count(body),
+            ("POST", ["corpus", name]) => self.add_to_corpus(name, body),
+            ("GET", ["corpus", name, "top"]) => self.corpus_top(name, query),
+            ("DELETE", ["corpus", name]) => self.delete_corpus(name),
+            (_, ["count"]) | (_, ["corpus", _]) | (_, ["corpus", _, "top"]) => {
+                Response::error(405, format!("method {method} not allowed on {path}"))
+            }
+            _ => Response::error(404, format!("no route for {path}")),
+        }
+    }
+
+    fn count(&self, body: &[u8]) -> Response {
+        let req = match parse_request(body) {
+            Ok(r) => r,
+            Err(e) => return Response::error(400, e),
+        };
+        match (req.count(), req.locale()) {
+            (Ok(counter), Ok(locale)) => Response::ok(report(&counter, req.top, &locale)),
+            (Err(e), _) | (_, Err(e)) => Response::error(400, e),
+        }
+    }
+
+    fn add_to_corpus(&self, name: &str, body: &[u8]) -> Response {
+        if !valid_name(name) {
+            return Response::error(400, format!("invalid corpus name '{name}'"));
+        }
+        let counter = match parse_request(body).and_then(|r| r.count()) {
+            Ok(c) => c,
+            Err(e) => return Response::error(400, e),
+        };
+
+        let mut corpora = self.corpora.lock().unwrap_or_else(|e| e.into_inner());
+        let corpus = corpora
+            .entry(name.to_string())
+            .or_insert_with(|| WordCounter::with_tokenizer(counter.tokenizer().clone()));
+        // comme `--load` : on ne mélange pas des mots et des octets
+        let (expected, found) = (
+            Vocabulary::of(corpus.tokenizer()),
+            Vocabulary::of(counter.tokenizer()),
+        );
+        if found != expected {
+            return Response::error(
+                409,
+                format!("corpus '{name}' was counted with {expected}, not {found}"),
+            );
+        }
+        let added = counter.total();
+        corpus.merge(counter);
+        Response::ok(json!({
+            "name": name,
+            "added": added,
+            "total": corpus.total(),
+            "unique": corpus.unique(),
+        }))
+    }
+
+    fn corpus_top(&self, name: &str, query: &str) -> Response {
+        let params: HashMap<&str, &str> = query
+            .split('&')
+            .filter_map(|kv| kv.split_once('='))
+            .collect();
+        let n = match params.get("n").map(|v| v.parse::<usize>()) {
+            None => 10,
+            Some(Ok(n)) => n,
+            Some(Err(_)) => return Response::error(400, "invalid n: expected a number"),
+        };
+        let locale = match LocaleSettings::from_name(params.get("locale").copied().unwrap_or("en"))
+        {
+            Ok(l) => l,
+            Err(e) => return Response::error(400, format!("invalid locale: {e}")),
+        };
+
+        let corpora = self.corpora.lock().unwrap_or_else(|e| e.into_inner());
+        match corpora.get(name) {
+            Some(corpus) => {
+                let mut body = report(corpus, Some(n), &locale);
+                body["name"] = json!(name);
+                Response::ok(body)
+            }
+            None => Response::error(404, format!("no corpus named '{name}'")),
+        }
+    }
+
+    fn delete_corpus(&self, name: &str) -> Response {
+        let mut corpora = self.corpora.lock().unwrap_or_else(|e| e.into_inner());
+        match corpora.remove(name) {
+            Some(_) => Response::ok(json!({ "name": name, "deleted": true })),
+            None => Response::error(404, format!("no corpus named '{name}'")),
+        }
+    }
+}
+
+fn parse_request(body: &[u8]) -> Result<CountRequest, String> {
+    let text = std::str::from_utf8(body).map_err(|_| "request body is not valid UTF-8")?;
+    if text.trim_start().starts_with('{') {
+        serde_json::from_str(text).map_err(|e| format!("invalid JSON request: {e}"))
+    } else {
+        Ok(CountRequest {
+            text: text.to_string(),
+            ..CountRequest::default()
+        })
+    }
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// `{"total", "unique", "words": [{"word", "count"}...]}`, mots triés comme
+/// dans le rapport de la CLI
+fn report(counter: &WordCounter, top: Option<usize>, locale: &LocaleSettings) -> Value {
+    let ranked = counter.ranked(&locale.collator);
+    let words: Vec<Value> = ranked
+        .into_iter()
+        .take(top.unwrap_or(usize::MAX))
+        .map(|(w, c)| json!({ "word": w, "count": c }))
+        .collect();
+    json!({
+        "total": counter.total(),
+        "unique": counter.unique(),
+        "words": words,
+    })
+}
+
+/// Écoute sur `listener` jusqu'à l'arrêt du processus
+pub fn serve(listener: TcpListener) -> io::Result<()> {
+    let server = Arc::new(Server::new());
+    let active = Arc::new(AtomicUsize::new(0));
+    for stream in listener.incoming() {
+        let mut stream = match stream {
+            Ok(s) => s,
+            Err(e) => {
+                eprintln!("wordfreq: connection failed: {e}");
+                continue;
+            }
+        };
+        if active.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+            active.fetch_sub(1, Ordering::SeqCst);
+            let _ = stream.set_write_timeout(Some(Duration::from_secs(1)));
+            let resp = Response::error(503, "too many connections, retry later");
+            let _ = write_response(&mut stream, &resp);
+            continue;
+        }
+        let (server, slot) = (Arc::clone(&server), Slot(Arc::clone(&active)));
+        thread::spawn(move || {
+            let _slot = slot;
+            if let Err(e) = handle_connection(&server, stream) {
+                eprintln!("wordfreq: {e}");
+            }
+        });
+    }
+    Ok(())
+}
+
+/// Une connexion comptée dans `active`, rendue même si son thread panique
+struct Slot(Arc<AtomicUsize>);
+
+impl Drop for Slot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn handle_connection(server: &Server, stream: TcpStream) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(30)))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return write_response(&mut writer, &Response::error(400, "malformed request line"));
+    };
+
+    let mut content_length = 0;
+    let mut chunked = false;
+    let mut expect_continue = false;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+            break;
+        }
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match name.trim().to_ascii_lowercase().as_str() {
+            "content-length" => content_length = value.parse().unwrap_or(usize::MAX),
+            "transfer-encoding" => chunked = value.eq_ignore_ascii_case("chunked"),
+            "expect" => expect_continue = value.eq_ignore_ascii_case("100-continue"),
+            _ => {}
+        }
+    }
+
+    if chunked {
+        let resp = Response::error(411, "chunked bodies are not supported, send Content-Length");
+        return write_response(&mut writer, &resp);
+    }
+    if content_length > MAX_BODY {
+        let resp = Response::error(413, format!("body larger than {MAX_BODY} bytes"));
+        return write_response(&mut writer, &resp);
+    }
+    if expect_continue {
+        writer.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")?;
+    }
+
+    // lu au fil de l'eau : la mémoire suit ce qui arrive, pas ce qui est annoncé
+    let mut body = Vec::new();
+    reader
+        .by_ref()
+        .take(content_length as u64)
+        .read_to_end(&mut body)?;
+    if body.len() < content_length {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("body ended after {} of {content_length} bytes", body.len()),
+        ));
+    }
+    write_response(&mut writer, &server.handle(method, target, &body))
+}
+
+fn write_response(out: &mut impl Write, resp: &Response) -> io::Result<()> {
+    let reason = match resp.status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        503 => "Service Unavailable",
+        _ => "Error",
+    };
+    let body = format!("{}\n", resp.body);
+    write!(
+        out,
+        "HTTP/1.1 {} {reason}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        resp.status,
+        body.len()
+    )?;
+    out.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_with_cli_options() {
+        let server = Server::new();
+        let body = br#"{"text": "The cat and THE hat 42", "ignore_case": true, "no_numbers": true, "top": 2}"#;
+        let resp = server.handle("POST", "/count", body);
+
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.body["total"], 5);
+        assert_eq!(
+            resp.body["words"],
+            json!([{"word": "the", "count": 2}, {"word": "and", "count": 1}])
+        );
+    }
+
+    #[test]
+    fn count_bytes() {
+        let server = Server::new();
+        let resp = server.handle(
+            "POST",
+            "/count",
+            br#"{"text": "\u00e9\u00e9 a", "unit": "byte"}"#,
+        );
+
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.body["total"], 6);
+        assert_eq!(
+            resp.body["words"],
+            json!([
+                {"word": "0xa9", "count": 2},
+                {"word": "0xc3", "count": 2},
+                {"word": "0x20", "count": 1},
+                {"word": "0x61 'a'", "count": 1}
+            ])
+        );
+    }
+
+    #[test]
+    fn plain_text_and_errors() {
+        let server = Server::new();
+        assert_eq!(server.handle("POST", "/count", b"a b a").body["unique"], 2);
+        assert_eq!(server.handle("POST", "/count", b"{\"txt\": 1}").status, 400);
+        assert_eq!(
+            server
+                .handle("POST", "/count", br#"{"text": "a", "unit": "x"}"#)
+                .status,
+            400
+        );
+        assert_eq!(server.handle("GET", "/count", b"").status, 405);
+        assert_eq!(server.handle("GET", "/nope", b"").status, 404);
+    }
+
+    #[test]
+    fn named_corpus_accumulates() {
+        let server = Server::new();
+        server.handle("POST", "/corpus/logs", b"error timeout");
+        let resp = server.handle("POST", "/corpus/logs", br#"{"text": "error disk"}"#);
+        assert_eq!(resp.body["total"], 4);
+
+        let top = server.handle("GET", "/corpus/logs/top?n=1", b"");
+        assert_eq!(top.body["words"], json!([{"word": "error", "count": 2}]));
+
+        assert_eq!(server.handle("DELETE", "/corpus/logs", b"").status, 200);
+        assert_eq!(server.handle("GET", "/corpus/logs/top", b"").status, 404);
+        assert_eq!(server.handle("POST", "/corpus/a%20b", b"x").status, 400);
+    }
+
+    #[test]
+    fn connections_are_capped() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        thread::spawn(move || serve(listener));
+
+        // des clients qui annoncent un gros corps sans l'envoyer
+        let idle: Vec<TcpStream> = (0..MAX_CONNECTIONS)
+            .map(|_| {
+                let mut s = TcpStream::connect(addr).unwrap();
+                s.write_all(b"POST /count HTTP/1.1\r\nContent-Length: 60000000\r\n\r\nab")
+                    .unwrap();
+                s
+            })
+            .collect();
+        let mut extra = TcpStream::connect(addr).unwrap();
+        let mut resp = String::new();
+        extra.read_to_string(&mut resp).unwrap();
+        assert!(resp.starts_with("HTTP/1.1 503"), "{resp}");
+
+        // une place se libère dès qu'un client part
+        drop(idle);
+        let ok = (0..50).any(|_| {
+            thread::sleep(Duration::from_millis(20));
+            let mut s = TcpStream::connect(addr).unwrap();
+            s.write_all(b"POST /count HTTP/1.1\r\nContent-Length: 3\r\n\r\na b")
+                .unwrap();
+            let mut resp = String::new();
+            s.read_to_string(&mut resp).is_ok() && resp.starts_with("HTTP/1.1 200")
+        });
+        assert!(ok);
+    }
+
+    #[test]
+    fn corpus_keeps_its_vocabulary() {
+        let server = Server::new();
+        server.handle("POST", "/corpus/raw", br#"{"text": "a b", "unit": "byte"}"#);
+
+        let resp = server.handle("POST", "/corpus/raw", br#"{"text": "a b"}"#);
+        assert_eq!(resp.status, 409);
+        let resp = server.handle(
+            "POST",
+            "/corpus/raw",
+            br#"{"text": "a", "unit": "byte", "ignore_case": true}"#,
+        );
+        assert_eq!(resp.status, 409);
+
+        // mêmes clés : les filtres de mots peuvent varier d'une requête à l'autre
+        let resp = server.handle("POST", "/corpus/raw", br#"{"text": "a", "unit": "byte"}"#);
+        assert_eq!(resp.status, 200);
+        assert_eq!(resp.body["total"], 4);
+        let top = server.handle("GET", "/corpus/raw/top?n=1", b"");
+        assert_eq!(top.body["words"], json!([{"word": "0x61 'a'", "count": 2}]));
+    }
+}
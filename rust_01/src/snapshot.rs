@@ -0,0 +1,307 @@
+//! Sauvegarde binaire des compteurs (`--save` / `--load` / `merge`).
+//!
+//! Format (version 1), tous les entiers en varint LEB128 :
+//!
+//! ```text
+//! "WFQS" | version: u8 | unité: u8 | code: u8 | options: u8
+//!        | total | nb d'entrées | (len, mot UTF-8, fréquence)*
+//! ```
+//!
+//! L'en-tête garde le [`Vocabulary`] du compteur : un snapshot de mots ne
+//! doit pas être fusionné avec un comptage de caractères. Les entrées sont
+//! triées par mot pour qu'un même état donne toujours le même fichier.
+
+use crate::code::CodeMode;
+use crate::counter::WordCounter;
+use crate::tokenizer::{Tokenizer, Unit};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+const MAGIC: &[u8; 4] = b"WFQS";
+const VERSION: u8 = 1;
+
+const UNITS: [Unit; 4] = [Unit::Word, Unit::Char, Unit::Grapheme, Unit::Byte];
+const CODE_MODES: [Option<CodeMode>; 4] = [
+    None,
+    Some(CodeMode::Split),
+    Some(CodeMode::Whole),
+    Some(CodeMode::Both),
+];
+const IGNORE_CASE: u8 = 1;
+
+/// Les options qui changent la nature des clés comptées (`--unit`, `--code`,
+/// `--ignore-case`) : deux compteurs ne se fusionnent que si elles sont égales
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vocabulary {
+    pub unit: Unit,
+    pub code: Option<CodeMode>,
+    pub ignore_case: bool,
+}
+
+impl Vocabulary {
+    pub fn of(tokenizer: &Tokenizer) -> Self {
+        Self {
+            unit: tokenizer.get_unit(),
+            code: tokenizer.get_code(),
+            ignore_case: tokenizer.is_ignore_case(),
+        }
+    }
+
+    /// Un `Tokenizer` qui produit ce vocabulaire
+    pub fn tokenizer(self) -> Tokenizer {
+        Tokenizer::new()
+            .unit(self.unit)
+            .code(self.code)
+            .ignore_case(self.ignore_case)
+    }
+}
+
+/// Sous la forme des options de la ligne de commande : "--unit word --code split"
+impl fmt::Display for Vocabulary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "--unit {}", self.unit.name())?;
+        if let Some(mode) = self.code {
+            let name = match mode {
+                CodeMode::Split => "split",
+                CodeMode::Whole => "whole",
+                CodeMode::Both => "both",
+            };
+            write!(f, " --code {name}")?;
+        }
+        if self.ignore_case {
+            write!(f, " --ignore-case")?;
+        }
+        Ok(())
+    }
+}
+
+/// Écrit un snapshot dans `path`. Le fichier n'est remplacé qu'une fois le
+/// nouveau entièrement écrit (fichier temporaire puis `rename`) : une erreur
+/// en cours de route laisse l'ancien intact, même avec `--load F --save F`.
+pub fn save<P: AsRef<Path>>(path: P, counter: &WordCounter) -> io::Result<()> {
+    let path = path.as_ref();
+    let name = path
+        .file_name()
+        .ok_or_else(|| invalid("snapshot path has no file name"))?;
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(name);
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp = path.with_file_name(tmp_name);
+
+    let written = (|| {
+        let mut w = BufWriter::new(File::create(&tmp)?);
+        write_snapshot(&mut w, counter)?;
+        let file = w.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    })();
+    if written.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    written
+}
+
+/// Lit un snapshot écrit par [`save`] ; le compteur a le `Tokenizer` de son
+/// [`Vocabulary`]
+pub fn load<P: AsRef<Path>>(path: P) -> io::Result<WordCounter> {
+    let mut r = BufReader::new(File::open(path)?);
+    read_snapshot(&mut r)
+}
+
+pub fn write_snapshot<W: Write>(w: &mut W, counter: &WordCounter) -> io::Result<()> {
+    let mut entries: Vec<(&str, usize)> = counter.iter().collect();
+    entries.sort_unstable_by(|a, b| a.0.cmp(b.0));
+
+    let vocabulary = Vocabulary::of(counter.tokenizer());
+    let unit = UNITS.iter().position(|u| *u == vocabulary.unit);
+    let code = CODE_MODES.iter().position(|m| *m == vocabulary.code);
+    let options = if vocabulary.ignore_case {
+        IGNORE_CASE
+    } else {
+        0
+    };
+
+    w.write_all(MAGIC)?;
+    w.write_all(&[VERSION])?;
+    w.write_all(&[
+        unit.expect("every unit has a code") as u8,
+        code.expect("every code mode has a code") as u8,
+        options,
+    ])?;
+    write_varint(w, counter.total() as u64)?;
+    write_varint(w, entries.len() as u64)?;
+
+    for (word, count) in entries {
+        write_varint(w, word.len() as u64)?;
+        w.write_all(word.as_bytes())?;
+        write_varint(w, count as u64)?;
+    }
+
+    Ok(())
+}
+
+pub fn read_snapshot<R: Read>(r: &mut R) -> io::Result<WordCounter> {
+    let mut magic = [0u8; 4];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a wordfreq snapshot"));
+    }
+
+    let mut version = [0u8; 1];
+    r.read_exact(&mut version)?;
+    if version[0] != VERSION {
+        return Err(invalid(&format!(
+            "unsupported snapshot version {} (expected {VERSION})",
+            version[0]
+        )));
+    }
+
+    let mut header = [0u8; 3];
+    r.read_exact(&mut header)?;
+    let vocabulary = Vocabulary {
+        unit: *UNITS
+            .get(header[0] as usize)
+            .ok_or_else(|| invalid("unknown unit"))?,
+        code: *CODE_MODES
+            .get(header[1] as usize)
+            .ok_or_else(|| invalid("unknown code mode"))?,
+        ignore_case: header[2] & IGNORE_CASE != 0,
+    };
+
+    let total = read_varint(r)? as usize;
+    let entries = read_varint(r)?;
+
+    let mut counter = WordCounter::with_tokenizer(vocabulary.tokenizer());
+    let mut sum = 0usize;
+    for _ in 0..entries {
+        let len = read_varint(r)?;
+        // pas de `vec![0; len]` : une longueur corrompue ne doit pas allouer des Go
+        let mut buf = Vec::new();
+        r.by_ref().take(len).read_to_end(&mut buf)?;
+        if buf.len() as u64 != len {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        let word = String::from_utf8(buf).map_err(|_| invalid("word is not valid UTF-8"))?;
+        let count = read_varint(r)? as usize;
+
+        counter.add(&word, count);
+        sum += count;
+    }
+
+    if sum != total {
+        return Err(invalid("total does not match the sum of the counts"));
+    }
+
+    Ok(counter)
+}
+
+fn write_varint<W: Write>(w: &mut W, mut v: u64) -> io::Result<()> {
+    let mut buf = [0u8; 10];
+    let mut i = 0;
+    loop {
+        let byte = (v & 0x7f) as u8;
+        v >>= 7;
+        if v == 0 {
+            buf[i] = byte;
+            i += 1;
+            break;
+        }
+        buf[i] = byte | 0x80;
+        i += 1;
+    }
+    w.write_all(&buf[..i])
+}
+
+fn read_varint<R: Read>(r: &mut R) -> io::Result<u64> {
+    let mut result = 0u64;
+    for shift in (0..64).step_by(7) {
+        let mut byte = [0u8; 1];
+        r.read_exact(&mut byte)?;
+        result |= u64::from(byte[0] & 0x7f) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(result);
+        }
+    }
+    Err(invalid("varint too long"))
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut c = WordCounter::new();
+        c.feed("un deux deux trois trois trois été");
+
+        let mut buf = Vec::new();
+        write_snapshot(&mut buf, &c).unwrap();
+        let back = read_snapshot(&mut buf.as_slice()).unwrap();
+
+        assert_eq!(back.total(), 7);
+        assert_eq!(back.get("trois"), 3);
+        assert_eq!(back.get("été"), 1);
+    }
+
+    #[test]
+    fn vocabulary_is_kept() {
+        let tokenizer = Tokenizer::new().unit(Unit::Char).ignore_case(true);
+        let mut c = WordCounter::with_tokenizer(tokenizer);
+        c.feed("Aa");
+
+        let mut buf = Vec::new();
+        write_snapshot(&mut buf, &c).unwrap();
+        let back = read_snapshot(&mut buf.as_slice()).unwrap();
+
+        let vocabulary = Vocabulary::of(back.tokenizer());
+        assert_eq!(vocabulary, Vocabulary::of(c.tokenizer()));
+        assert_ne!(vocabulary, Vocabulary::of(&Tokenizer::new()));
+        assert_eq!(vocabulary.to_string(), "--unit char --ignore-case");
+        assert_eq!(back.get("a"), 2);
+    }
+
+    #[test]
+    fn save_replaces_the_file_atomically() {
+        let dir = std::env::temp_dir().join(format!("wfqs-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("counts.bin");
+
+        let mut c = WordCounter::new();
+        c.feed("a b");
+        save(&path, &c).unwrap();
+        c.feed("b");
+        save(&path, &c).unwrap();
+
+        assert_eq!(load(&path).unwrap().get("b"), 2);
+        // pas de fichier temporaire laissé derrière
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn varint_edges() {
+        for v in [0u64, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut buf = Vec::new();
+            write_varint(&mut buf, v).unwrap();
+            assert_eq!(read_varint(&mut buf.as_slice()).unwrap(), v);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_magic_and_truncated_files() {
+        assert!(read_snapshot(&mut &b"NOPE\x01\x00\x00\x00\x00\x00"[..]).is_err());
+
+        let mut c = WordCounter::new();
+        c.feed("hello world");
+        let mut buf = Vec::new();
+        write_snapshot(&mut buf, &c).unwrap();
+        buf.truncate(buf.len() - 2);
+        assert!(read_snapshot(&mut buf.as_slice()).is_err());
+    }
+}
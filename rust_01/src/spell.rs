@@ -0,0 +1,176 @@
+//! Mots inconnus d'un dictionnaire (`--dictionary`) et suggestions par
+//! distance d'édition. Chaque mot distinct n'est vérifié qu'une fois, à
+//! partir des compteurs.
+
+use crate::counter::WordCounter;
+use crate::locale::Collator;
+use std::collections::{HashMap, HashSet};
+
+/// Une liste de mots, un par ligne (lignes vides et `#` ignorées)
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+    /// mots groupés par nombre de caractères, pour ne comparer que les
+    /// longueurs compatibles avec la distance maximale
+    by_len: HashMap<usize, Vec<String>>,
+}
+
+impl Dictionary {
+    pub fn parse(text: &str) -> Self {
+        let mut dict = Self::default();
+        for line in text.lines() {
+            let w = line.trim();
+            if w.is_empty() || w.starts_with('#') {
+                continue;
+            }
+            if dict.words.insert(w.to_string()) {
+                dict.by_len
+                    .entry(w.chars().count())
+                    .or_default()
+                    .push(w.to_string());
+            }
+        }
+        dict
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Connu tel quel ou en minuscules ("The" est connu si "the" l'est)
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word) || self.words.contains(&word.to_lowercase())
+    }
+
+    /// Jusqu'à `n` mots du dictionnaire à au plus `max_distance` éditions,
+    /// les plus proches d'abord (puis par ordre alphabétique)
+    pub fn suggest(&self, word: &str, max_distance: usize, n: usize) -> Vec<&str> {
+        let lower = word.to_lowercase();
+        let target: Vec<char> = lower.chars().collect();
+        let len = target.len();
+
+        let mut found: Vec<(usize, &str)> = Vec::new();
+        for l in len.saturating_sub(max_distance)..=len + max_distance {
+            for candidate in self.by_len.get(&l).into_iter().flatten() {
+                let c: Vec<char> = candidate.to_lowercase().chars().collect();
+                if let Some(d) = edit_distance(&target, &c, max_distance) {
+                    found.push((d, candidate));
+                }
+            }
+        }
+
+        found.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+        found.dedup_by(|a, b| a.1 == b.1);
+        found.into_iter().take(n).map(|(_, w)| w).collect()
+    }
+}
+
+/// Distance de Damerau-Levenshtein restreinte (insertion, suppression,
+/// substitution, transposition de deux voisins), ou `None` si elle dépasse
+/// `max`
+pub fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+
+    // trois lignes de la matrice suffisent pour la transposition
+    let mut prev2: Vec<usize> = Vec::new();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(prev2[j - 2] + 1);
+            }
+        }
+        // toute la ligne dépasse déjà : inutile de continuer
+        if row.iter().all(|&d| d > max) {
+            return None;
+        }
+        prev2 = std::mem::replace(&mut prev, row);
+    }
+
+    let d = prev[b.len()];
+    (d <= max).then_some(d)
+}
+
+/// Un mot compté absent du dictionnaire
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unknown<'a> {
+    pub word: &'a str,
+    pub count: usize,
+    pub suggestions: Vec<&'a str>,
+}
+
+/// Les mots de `counter` absents de `dict`, du plus fréquent au moins
+/// fréquent. Seuls les mots faits de lettres (et d'apostrophes ou de tirets
+/// internes) sont vérifiés : nombres, identifiants et citations sont ignorés.
+pub fn unknown_words<'a>(
+    counter: &'a WordCounter,
+    dict: &'a Dictionary,
+    collator: &Collator,
+    max_distance: usize,
+) -> Vec<Unknown<'a>> {
+    counter
+        .ranked(collator)
+        .into_iter()
+        .filter(|(w, _)| is_checkable(w) && !dict.contains(w))
+        .map(|(word, count)| Unknown {
+            word,
+            count,
+            // les mots très courts n'ont que des voisins trop lointains
+            suggestions: dict.suggest(word, max_distance.min(word.chars().count() / 2), 3),
+        })
+        .collect()
+}
+
+fn is_checkable(word: &str) -> bool {
+    let inner = word.trim_matches(|c| c == '\'' || c == '-');
+    inner == word
+        && word.chars().any(char::is_alphabetic)
+        && word
+            .chars()
+            .all(|c| c.is_alphabetic() || c == '\'' || c == '’' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dist(a: &str, b: &str) -> Option<usize> {
+        let a: Vec<char> = a.chars().collect();
+        let b: Vec<char> = b.chars().collect();
+        edit_distance(&a, &b, 2)
+    }
+
+    #[test]
+    fn distances() {
+        assert_eq!(dist("cat", "cat"), Some(0));
+        assert_eq!(dist("teh", "the"), Some(1));
+        assert_eq!(dist("recieve", "receive"), Some(1));
+        assert_eq!(dist("kitten", "sitting"), None);
+        assert_eq!(dist("été", "ete"), Some(2));
+    }
+
+    #[test]
+    fn unknown_words_with_suggestions() {
+        let dict = Dictionary::parse("# mots\nthe\ncat\ncar\nsat\nmat\n\nhat\n");
+        assert_eq!(dict.len(), 6);
+
+        let mut counter = WordCounter::new();
+        counter.feed("The caat sat on teh mat teh 42 'quoted'");
+        let unknown = unknown_words(&counter, &dict, &Collator::default(), 2);
+
+        let words: Vec<(&str, usize)> = unknown.iter().map(|u| (u.word, u.count)).collect();
+        assert_eq!(words, vec![("teh", 2), ("caat", 1), ("on", 1)]);
+        assert_eq!(unknown[0].suggestions, vec!["the"]);
+        assert_eq!(unknown[1].suggestions, vec!["cat", "car", "hat"]);
+        assert!(unknown[2].suggestions.is_empty());
+    }
+}
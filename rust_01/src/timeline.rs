@@ -0,0 +1,347 @@
+//! Fréquences par tranche de temps pour les journaux (`--bucket`) : chaque
+//! ligne est datée par son horodatage, les mots sont comptés par tranche, et
+//! les pics (forte hausse d'une tranche à la suivante) sont signalés.
+
+use crate::tokenizer::Tokenizer;
+use regex::Regex;
+use std::collections::{BTreeMap, HashMap};
+
+/// Horodatages reconnus par défaut : ISO 8601 ("2024-03-01T12:00:00Z",
+/// "2024-03-01 12:00:00,123") et format Apache ("01/Mar/2024:12:00:00 +0100")
+pub const DEFAULT_TIMESTAMP: &str = r"\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}(?::\d{2})?(?:[.,]\d+)?(?:Z|[+-]\d{2}:?\d{2})?|\d{2}/[A-Z][a-z]{2}/\d{4}:\d{2}:\d{2}:\d{2}(?: [+-]\d{4})?";
+
+/// Nombre maximal de tranches d'une série : au-delà (un horodatage aberrant,
+/// une tranche trop fine), la série est refusée plutôt que d'épuiser la mémoire
+pub const MAX_BUCKETS: i64 = 100_000;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Lit une durée de tranche : "30s", "15m", "1h", "1d" (un nombre seul est
+/// en secondes)
+pub fn parse_duration(s: &str) -> Result<i64, String> {
+    let err = || format!("invalid duration '{s}' (expected e.g. 30s, 15m, 1h, 1d)");
+    let (digits, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let scale = match unit {
+        "" | "s" => 1,
+        "m" | "min" => 60,
+        "h" => 3600,
+        "d" => 86400,
+        _ => return Err(err()),
+    };
+    match digits.parse::<i64>() {
+        Ok(n) if n > 0 => n.checked_mul(scale).ok_or_else(err),
+        _ => Err(err()),
+    }
+}
+
+/// Jours depuis le 1970-01-01 (calendrier grégorien proleptique)
+fn days_from_civil(y: i64, m: i64, d: i64) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let doy = (153 * ((m + 9) % 12) + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+fn civil_from_days(z: i64) -> (i64, i64, i64) {
+    let z = z + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + i64::from(m <= 2), m, d)
+}
+
+/// Horodatages acceptés : de l'an 0 à la fin de l'an 9999 (UTC), ce que
+/// peuvent écrire les formats ISO et Apache ; au-delà, un nombre capturé
+/// par `--timestamp-regex` n'est pas une date
+pub const TIMESTAMPS: std::ops::RangeInclusive<i64> = -62_167_219_200..=253_402_300_799;
+
+fn days_in_month(y: i64, m: i64) -> i64 {
+    match m {
+        2 if y % 4 == 0 && (y % 100 != 0 || y % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Secondes depuis l'époque Unix (UTC) d'un horodatage ISO 8601, Apache ou
+/// d'un nombre de secondes Unix ; `None` si le format n'est pas reconnu ou
+/// si la date ou l'heure n'existe pas ("2024-02-30", "25:00")
+pub fn parse_timestamp(s: &str) -> Option<i64> {
+    let s = s.trim();
+    if s.len() >= 9 && s.chars().all(|c| c.is_ascii_digit()) {
+        return s.parse().ok().filter(|t| TIMESTAMPS.contains(t));
+    }
+
+    let num = |r: std::ops::Range<usize>| s.get(r)?.parse::<i64>().ok();
+    let (date, time_at) = if s.as_bytes().get(4) == Some(&b'-') {
+        // 2024-03-01T12:00:00
+        ((num(0..4)?, num(5..7)?, num(8..10)?), 11)
+    } else if s.as_bytes().get(2) == Some(&b'/') {
+        // 01/Mar/2024:12:00:00
+        let month = MONTHS.iter().position(|m| s.get(3..6) == Some(m))? as i64 + 1;
+        ((num(7..11)?, month, num(0..2)?), 12)
+    } else {
+        return None;
+    };
+
+    let (y, m, d) = date;
+    if !(1..=12).contains(&m) || !(1..=days_in_month(y, m)).contains(&d) {
+        return None;
+    }
+    let hour = num(time_at..time_at + 2)?;
+    let min = num(time_at + 3..time_at + 5)?;
+    let sec = if s.as_bytes().get(time_at + 5) == Some(&b':') {
+        num(time_at + 6..time_at + 8)?
+    } else {
+        0
+    };
+    // 60 : seconde intercalaire
+    if hour > 23 || min > 59 || sec > 60 {
+        return None;
+    }
+
+    // décalage horaire éventuel : "Z", "+02:00", " +0100"
+    let rest = &s[(time_at + 5).min(s.len())..];
+    let offset = match rest.rfind(['+', '-']) {
+        Some(i) => {
+            let digits: String = rest[i + 1..].chars().filter(char::is_ascii_digit).collect();
+            let (h, m) = (
+                digits.get(0..2)?.parse::<i64>().ok()?,
+                digits.get(2..4)?.parse::<i64>().ok()?,
+            );
+            if h > 23 || m > 59 {
+                return None;
+            }
+            let sign = if rest[i..].starts_with('-') { -1 } else { 1 };
+            sign * (h * 3600 + m * 60)
+        }
+        None => 0,
+    };
+
+    Some(days_from_civil(y, m, d) * 86400 + hour * 3600 + min * 60 + sec - offset)
+        .filter(|t| TIMESTAMPS.contains(t))
+}
+
+/// "2024-03-01 12:00" (UTC)
+pub fn format_time(t: i64) -> String {
+    let (y, m, d) = civil_from_days(t.div_euclid(86400));
+    let secs = t.rem_euclid(86400);
+    let (h, min, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if s == 0 {
+        format!("{y:04}-{m:02}-{d:02} {h:02}:{min:02}")
+    } else {
+        format!("{y:04}-{m:02}-{d:02} {h:02}:{min:02}:{s:02}")
+    }
+}
+
+/// Compteurs par tranche de temps
+#[derive(Debug, Clone)]
+pub struct Timeline {
+    bucket: i64,
+    timestamp: Regex,
+    /// début de tranche -> compteurs
+    buckets: BTreeMap<i64, HashMap<String, usize>>,
+    totals: HashMap<String, usize>,
+    /// horodatage de la dernière ligne datée, pour les lignes de suite
+    /// (traces de pile...)
+    current: Option<i64>,
+    /// lignes ignorées faute d'horodatage avant elles
+    pub undated_lines: usize,
+}
+
+impl Timeline {
+    /// `timestamp` trouve l'horodatage de chaque ligne ; s'il a un groupe
+    /// capturant, seul le premier groupe est interprété
+    pub fn new(bucket: i64, timestamp: Regex) -> Self {
+        Self {
+            bucket: bucket.max(1),
+            timestamp,
+            buckets: BTreeMap::new(),
+            totals: HashMap::new(),
+            current: None,
+            undated_lines: 0,
+        }
+    }
+
+    /// Compte les mots de chaque ligne de `text` dans la tranche de son
+    /// horodatage. L'horodatage lui-même n'est pas compté ; une ligne sans
+    /// horodatage reconnu appartient à la tranche de la ligne précédente.
+    pub fn feed(&mut self, tokenizer: &Tokenizer, text: &str) {
+        for line in text.lines() {
+            let found = self.timestamp.captures(line).and_then(|caps| {
+                let m = caps.get(1).or_else(|| caps.get(0))?;
+                Some((parse_timestamp(m.as_str())?, caps.get(0)?.range()))
+            });
+            let mut rest = line.to_string();
+            if let Some((t, range)) = found {
+                self.current = Some(t);
+                rest.replace_range(range, "");
+            }
+            let Some(t) = self.current else {
+                self.undated_lines += 1;
+                continue;
+            };
+
+            let start = t.div_euclid(self.bucket) * self.bucket;
+            let counts = self.buckets.entry(start).or_default();
+            for w in tokenizer.words(&rest) {
+                *counts.entry(w.to_string()).or_insert(0) += 1;
+                *self.totals.entry(w.into_owned()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Les `n` termes les plus fréquents sur toute la période
+    pub fn top_terms(&self, n: usize) -> Vec<&str> {
+        let mut items: Vec<(&str, usize)> =
+            self.totals.iter().map(|(w, c)| (w.as_str(), *c)).collect();
+        items.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        items.into_iter().take(n).map(|(w, _)| w).collect()
+    }
+
+    /// Une ligne par tranche, du début à la fin sans trou (les tranches vides
+    /// valent 0), avec le compte de chaque terme de `terms`. Erreur si la
+    /// série dépasse [`MAX_BUCKETS`] tranches.
+    pub fn series(&self, terms: &[&str]) -> Result<Vec<(i64, Vec<usize>)>, String> {
+        let (Some(&first), Some(&last)) = (self.buckets.keys().next(), self.buckets.keys().last())
+        else {
+            return Ok(Vec::new());
+        };
+        let n = (last - first) / self.bucket + 1;
+        if n > MAX_BUCKETS {
+            return Err(format!(
+                "{n} buckets from {} to {} (at most {MAX_BUCKETS}): use a larger --bucket",
+                format_time(first),
+                format_time(last)
+            ));
+        }
+
+        // jamais `t += bucket` : la dernière tranche peut frôler i64::MAX
+        let rows = (0..n)
+            .map(|k| {
+                let t = first + k * self.bucket;
+                let counts = self.buckets.get(&t);
+                let row = terms
+                    .iter()
+                    .map(|w| counts.and_then(|c| c.get(*w)).copied().unwrap_or(0))
+                    .collect();
+                (t, row)
+            })
+            .collect();
+        Ok(rows)
+    }
+}
+
+/// Les pics d'une série : (ligne, colonne) des cellules au moins `factor`
+/// fois plus grandes que la tranche précédente (comptée comme 1 si elle est
+/// vide) et d'au moins `min_count` occurrences
+pub fn spikes(series: &[(i64, Vec<usize>)], factor: f64, min_count: usize) -> Vec<(usize, usize)> {
+    let mut out = Vec::new();
+    for i in 1..series.len() {
+        for (j, &c) in series[i].1.iter().enumerate() {
+            let prev = series[i - 1].1[j].max(1) as f64;
+            if c >= min_count && c as f64 >= factor * prev {
+                out.push((i, j));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z"), Some(0));
+        assert_eq!(parse_timestamp("2024-03-01 12:30:15,123"), Some(1709296215));
+        assert_eq!(
+            parse_timestamp("2024-03-01T14:30:15+02:00"),
+            Some(1709296215)
+        );
+        assert_eq!(
+            parse_timestamp("01/Mar/2024:13:30:15 +0100"),
+            Some(1709296215)
+        );
+        assert_eq!(parse_timestamp("1709296215"), Some(1709296215));
+        assert_eq!(parse_timestamp("2024-13-01 00:00"), None);
+        assert_eq!(parse_timestamp("2024-02-29 00:00"), Some(1709164800));
+        assert_eq!(parse_timestamp("2023-02-29 00:00"), None);
+        assert_eq!(parse_timestamp("2024-02-31 00:00"), None);
+        assert_eq!(parse_timestamp("2024-04-31 00:00"), None);
+        assert_eq!(parse_timestamp("2024-03-01 99:00"), None);
+        assert_eq!(parse_timestamp("2024-03-01 12:75"), None);
+        assert_eq!(parse_timestamp("2024-03-01T12:00:00+25:00"), None);
+        assert_eq!(parse_timestamp("9223372036854775807"), None);
+        assert_eq!(parse_timestamp("253402300799"), Some(253_402_300_799));
+        assert_eq!(parse_timestamp("yesterday"), None);
+        assert_eq!(format_time(1709296200), "2024-03-01 12:30");
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("1h"), Ok(3600));
+        assert_eq!(parse_duration("15m"), Ok(900));
+        assert_eq!(parse_duration("2d"), Ok(172800));
+        assert_eq!(parse_duration("45"), Ok(45));
+        assert!(parse_duration("0h").is_err());
+        assert!(parse_duration("1w").is_err());
+    }
+
+    #[test]
+    fn buckets_with_gaps_and_spikes() {
+        let log = "\
+2024-03-01 10:05:00 INFO ok
+2024-03-01 10:40:00 ERROR timeout
+  at retry
+2024-03-01 12:10:00 ERROR timeout
+2024-03-01 12:11:00 ERROR timeout
+2024-03-01 12:12:00 ERROR timeout
+2024-03-01 12:13:00 ERROR disk
+";
+        let mut tl = Timeline::new(3600, Regex::new(DEFAULT_TIMESTAMP).unwrap());
+        tl.feed(&Tokenizer::new(), log);
+
+        let terms = tl.top_terms(2);
+        assert_eq!(terms, vec!["ERROR", "timeout"]);
+
+        let series = tl.series(&terms).unwrap();
+        let labels: Vec<String> = series.iter().map(|(t, _)| format_time(*t)).collect();
+        assert_eq!(
+            labels,
+            vec!["2024-03-01 10:00", "2024-03-01 11:00", "2024-03-01 12:00"]
+        );
+        assert_eq!(series[0].1, vec![1, 1]);
+        assert_eq!(series[1].1, vec![0, 0]);
+        assert_eq!(series[2].1, vec![4, 3]);
+        // la ligne de suite est comptée avec la précédente
+        assert_eq!(tl.totals["retry"], 1);
+
+        assert_eq!(spikes(&series, 3.0, 3), vec![(2, 0), (2, 1)]);
+    }
+
+    #[test]
+    fn outlier_timestamp_is_refused() {
+        let log = "1990-01-01 00:00:00 boot\n2024-03-01 12:00:00 boot\n";
+        let mut tl = Timeline::new(1, Regex::new(DEFAULT_TIMESTAMP).unwrap());
+        tl.feed(&Tokenizer::new(), log);
+
+        let err = tl.series(&["boot"]).unwrap_err();
+        assert!(err.contains("larger --bucket"), "{err}");
+
+        // avec des tranches d'un an, la série tient
+        let mut tl = Timeline::new(365 * 86400, Regex::new(DEFAULT_TIMESTAMP).unwrap());
+        tl.feed(&Tokenizer::new(), log);
+        assert_eq!(tl.series(&["boot"]).unwrap().len(), 35);
+    }
+}
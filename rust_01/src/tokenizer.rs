@@ -0,0 +1,570 @@
+use crate::code::{self, CodeLang, CodeMode};
+use crate::language::Language;
+use regex::Regex;
+use std::borrow::Cow;
+use std::str::FromStr;
+use std::sync::LazyLock;
+use unicode_normalization::char::is_combining_mark;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Gestion des mots avec guillemets + mots normaux
+// - "World"  → token = "World"
+// - 'Hello'  → token = 'Hello'
+// - hello    → token = hello
+const WORD_PATTERN: &str = r#""[^"]+"|'[^']+'|\w+"#;
+
+// Avec `Numbers::Mask`, les UUID et les nombres à virgule ou séparateurs
+// forment un seul token ("3.14", "1,000") au lieu d'être coupés en morceaux
+const MASK_PATTERN: &str = r#"(?i:[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12})\b|\d+(?:[.,]\d+)+\b|"[^"]+"|'[^']+'|\w+"#;
+
+// Identifiants de code (`--code`) : pas de citations, pas de nombres seuls
+const CODE_PATTERN: &str = r"[\p{L}_][\p{L}\p{N}_]*";
+
+/// Un seul caractère de `\w`, pour les cas rares que le scanner ne sait pas
+/// classer seul (chiffres d'autres écritures, connecteurs, ZWJ...)
+static WORD_CHAR: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\w$").expect("invalid regex"));
+
+fn is_word_char(c: char) -> bool {
+    if c.is_ascii() {
+        return c.is_ascii_alphanumeric() || c == '_';
+    }
+    // lettres latines accentuées (U+00C0 à U+024F, sauf × et ÷) : le cas
+    // courant hors ASCII, sans passer par les tables Unicode
+    if ('\u{c0}'..='\u{24f}').contains(&c) {
+        return c != '×' && c != '÷';
+    }
+    if c.is_alphabetic() || is_combining_mark(c) {
+        return true;
+    }
+    let mut buf = [0u8; 4];
+    WORD_CHAR.is_match(c.encode_utf8(&mut buf))
+}
+
+/// Scanner écrit à la main, équivalent à `WORD_PATTERN` mais sans passer par
+/// le moteur d'expressions régulières : les octets ASCII (le cas courant)
+/// sont classés directement, sans décoder de caractère. Seule différence :
+/// les lettres des écritures ajoutées à Unicode après les tables de `regex`
+/// sont reconnues ici, comme le fait `char::is_alphabetic`.
+struct Scanner<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+    fn token(&mut self, start: usize, end: usize) -> Token<'a> {
+        self.pos = end;
+        Token {
+            text: &self.text[start..end],
+            start,
+            end,
+        }
+    }
+
+    /// Fin de la citation ouverte en `start`, si elle est fermée et non vide
+    fn quoted(&self, start: usize, quote: u8) -> Option<usize> {
+        let rest = &self.text.as_bytes()[start + 1..];
+        let len = rest.iter().position(|&b| b == quote)?;
+        (len > 0).then_some(start + len + 2)
+    }
+
+    /// Fin du mot qui commence en `start`
+    fn word_end(&self, start: usize) -> usize {
+        let bytes = self.text.as_bytes();
+        let mut i = start;
+        while i < bytes.len() {
+            let b = bytes[i];
+            if b.is_ascii() {
+                if !(b.is_ascii_alphanumeric() || b == b'_') {
+                    break;
+                }
+                i += 1;
+            } else {
+                let c = self.text[i..].chars().next().expect("char boundary");
+                if !is_word_char(c) {
+                    break;
+                }
+                i += c.len_utf8();
+            }
+        }
+        i
+    }
+}
+
+impl<'a> Iterator for Scanner<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let bytes = self.text.as_bytes();
+        while self.pos < bytes.len() {
+            let start = self.pos;
+            let b = bytes[start];
+            if b.is_ascii() {
+                if b.is_ascii_alphanumeric() || b == b'_' {
+                    let end = self.word_end(start);
+                    return Some(self.token(start, end));
+                }
+                if (b == b'"' || b == b'\'')
+                    && let Some(end) = self.quoted(start, b)
+                {
+                    return Some(self.token(start, end));
+                }
+                self.pos += 1;
+            } else {
+                let c = self.text[start..].chars().next().expect("char boundary");
+                if is_word_char(c) {
+                    let end = self.word_end(start);
+                    return Some(self.token(start, end));
+                }
+                self.pos += c.len_utf8();
+            }
+        }
+        None
+    }
+}
+
+/// Minuscules, sans allocation si le mot l'est déjà (cas courant en ASCII)
+fn lowercase(token: &str) -> Cow<'_, str> {
+    if token
+        .bytes()
+        .all(|b| b.is_ascii() && !b.is_ascii_uppercase())
+    {
+        Cow::Borrowed(token)
+    } else {
+        Cow::Owned(token.to_lowercase())
+    }
+}
+
+/// Traitement des tokens numériques (`--no-numbers`, `--mask-numbers`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Numbers {
+    #[default]
+    Keep,
+    /// Ignore les tokens uniquement composés de chiffres
+    Drop,
+    /// Remplace nombres, identifiants hexadécimaux et UUID par `<NUM>`,
+    /// `<HEX>` et `<UUID>`
+    Mask,
+}
+
+/// Classe d'un token pour `Numbers::Mask`, ou `None` si c'est un mot
+fn number_class(token: &str) -> Option<&'static str> {
+    let is_hex = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit());
+
+    if token.len() == 36
+        && token.split('-').map(str::len).eq([8, 4, 4, 4, 12])
+        && token.split('-').all(is_hex)
+    {
+        return Some("<UUID>");
+    }
+    if token
+        .split(['.', ','])
+        .all(|p| !p.is_empty() && p.chars().all(|c| c.is_ascii_digit()))
+    {
+        return Some("<NUM>");
+    }
+    // "0x1f", ou un identifiant d'au moins 6 chiffres hexadécimaux mêlant
+    // chiffres et lettres ("a3f9c2", mais pas "deadbeef" ni "facade")
+    let hex_prefixed = token.len() > 2
+        && (token.starts_with("0x") || token.starts_with("0X"))
+        && is_hex(&token[2..]);
+    let hex_id = token.len() >= 6
+        && is_hex(token)
+        && token.chars().any(|c| c.is_ascii_digit())
+        && token.chars().any(|c| c.is_ascii_alphabetic());
+    (hex_prefixed || hex_id).then_some("<HEX>")
+}
+
+/// Ce que l'on compte (`--unit`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Unit {
+    #[default]
+    Word,
+    /// Caractères Unicode (hors blancs)
+    Char,
+    /// Graphèmes étendus : "é" décomposé ou un emoji composé comptent pour un
+    Grapheme,
+    /// Octets bruts de l'entrée, avant décodage
+    Byte,
+}
+
+impl Unit {
+    pub fn name(self) -> &'static str {
+        match self {
+            Unit::Word => "word",
+            Unit::Char => "char",
+            Unit::Grapheme => "grapheme",
+            Unit::Byte => "byte",
+        }
+    }
+
+    /// Nom au pluriel pour les titres des rapports
+    pub fn plural(self) -> &'static str {
+        match self {
+            Unit::Word => "words",
+            Unit::Char => "chars",
+            Unit::Grapheme => "graphemes",
+            Unit::Byte => "bytes",
+        }
+    }
+}
+
+impl FromStr for Unit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "word" => Ok(Unit::Word),
+            "char" => Ok(Unit::Char),
+            "grapheme" => Ok(Unit::Grapheme),
+            "byte" => Ok(Unit::Byte),
+            _ => Err(format!(
+                "unknown unit '{s}' (expected word, char, grapheme or byte)"
+            )),
+        }
+    }
+}
+
+/// Clé d'un octet dans les compteurs : "0x41 'A'" si imprimable, sinon "0xc3"
+pub fn byte_key(b: u8) -> String {
+    if b.is_ascii_graphic() {
+        format!("0x{b:02x} '{}'", b as char)
+    } else {
+        format!("0x{b:02x}")
+    }
+}
+
+/// Un token brut avec sa position (en octets) dans le texte source
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub text: &'a str,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Découpe le texte en mots et applique les options de normalisation
+/// (casse, longueur minimale) partagées par le comptage et les autres modes.
+#[derive(Debug, Clone)]
+pub struct Tokenizer {
+    re: Regex,
+    unit: Unit,
+    ignore_case: bool,
+    min_length: Option<usize>,
+    stopwords: Option<&'static Language>,
+    numbers: Numbers,
+    include: Option<Regex>,
+    exclude: Option<Regex>,
+    code: Option<CodeMode>,
+    code_lang: Option<CodeLang>,
+}
+
+impl Default for Tokenizer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Tokenizer {
+    pub fn new() -> Self {
+        Self {
+            re: Regex::new(WORD_PATTERN).expect("invalid regex"),
+            unit: Unit::Word,
+            ignore_case: false,
+            min_length: None,
+            stopwords: None,
+            numbers: Numbers::Keep,
+            include: None,
+            exclude: None,
+            code: None,
+            code_lang: None,
+        }
+    }
+
+    /// Unité de comptage. Avec `Unit::Byte`, `tokens` découpe en caractères :
+    /// les octets bruts se comptent avec `WordCounter::feed_bytes`.
+    pub fn unit(mut self, unit: Unit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    pub fn get_unit(&self) -> Unit {
+        self.unit
+    }
+
+    /// Compte "Hello" et "hello" comme le même mot
+    pub fn ignore_case(mut self, yes: bool) -> Self {
+        self.ignore_case = yes;
+        self
+    }
+
+    pub fn is_ignore_case(&self) -> bool {
+        self.ignore_case
+    }
+
+    /// Ignore les mots de moins de `min` caractères
+    pub fn min_length(mut self, min: Option<usize>) -> Self {
+        self.min_length = min;
+        self
+    }
+
+    /// Ignore les mots-outils de `language` ("the", "and"...), quelle que
+    /// soit leur casse
+    pub fn stopwords(mut self, language: Option<&'static Language>) -> Self {
+        self.stopwords = language;
+        self
+    }
+
+    /// Traitement des nombres ; `Numbers::Mask` regroupe aussi les UUID et
+    /// les nombres décimaux en un seul token
+    pub fn numbers(mut self, numbers: Numbers) -> Self {
+        self.numbers = numbers;
+        self.with_pattern()
+    }
+
+    /// Découpe des identifiants de code source plutôt que des mots (les
+    /// mots-clés des langages courants sont ignorés)
+    pub fn code(mut self, mode: Option<CodeMode>) -> Self {
+        self.code = mode;
+        self.with_pattern()
+    }
+
+    /// Langage dont les mots-clés sont ignorés en mode `code` ; sans
+    /// langage, aucun identifiant n'est écarté
+    pub fn code_lang(mut self, lang: Option<CodeLang>) -> Self {
+        self.code_lang = lang;
+        self
+    }
+
+    pub fn get_code(&self) -> Option<CodeMode> {
+        self.code
+    }
+
+    fn with_pattern(mut self) -> Self {
+        let pattern = match (self.code, self.numbers) {
+            (Some(_), _) => CODE_PATTERN,
+            (None, Numbers::Mask) => MASK_PATTERN,
+            _ => WORD_PATTERN,
+        };
+        self.re = Regex::new(pattern).expect("invalid regex");
+        self
+    }
+
+    /// Ne garde que les mots (normalisés) qui contiennent une correspondance
+    pub fn matching(mut self, re: Option<Regex>) -> Self {
+        self.include = re;
+        self
+    }
+
+    /// Ignore les mots (normalisés) qui contiennent une correspondance
+    pub fn excluding(mut self, re: Option<Regex>) -> Self {
+        self.exclude = re;
+        self
+    }
+
+    /// Tous les tokens du texte, sans filtre ni normalisation (sauf les
+    /// mots-clés du `code_lang` en mode `code`, écartés avant le découpage
+    /// en sous-mots)
+    pub fn tokens<'a>(&'a self, text: &'a str) -> Box<dyn Iterator<Item = Token<'a>> + 'a> {
+        let span = |(start, s): (usize, &'a str)| Token {
+            text: s,
+            start,
+            end: start + s.len(),
+        };
+
+        match self.unit {
+            Unit::Word if let Some(mode) = self.code => Box::new(
+                self.re
+                    .find_iter(text)
+                    .filter(|m| !self.code_lang.is_some_and(|l| l.is_keyword(m.as_str())))
+                    .flat_map(move |m| {
+                        code::parts(m.as_str(), mode)
+                            .into_iter()
+                            .map(move |r| span((m.start() + r.start, &m.as_str()[r])))
+                    }),
+            ),
+            Unit::Word if self.numbers != Numbers::Mask => Box::new(Scanner { text, pos: 0 }),
+            Unit::Word => Box::new(self.re.find_iter(text).map(|m| Token {
+                text: m.as_str(),
+                start: m.start(),
+                end: m.end(),
+            })),
+            Unit::Char | Unit::Byte => Box::new(
+                text.char_indices()
+                    .filter(|(_, c)| !c.is_whitespace())
+                    .map(move |(i, c)| span((i, &text[i..i + c.len_utf8()]))),
+            ),
+            Unit::Grapheme => Box::new(
+                text.grapheme_indices(true)
+                    .filter(|(_, g)| !g.chars().all(char::is_whitespace))
+                    .map(span),
+            ),
+        }
+    }
+
+    /// Normalise un token, ou `None` s'il doit être ignoré
+    pub fn normalize<'a>(&self, token: &'a str) -> Option<Cow<'a, str>> {
+        let word = self.normalize_word(token)?;
+
+        if let Some(re) = &self.include
+            && !re.is_match(&word)
+        {
+            return None;
+        }
+        if let Some(re) = &self.exclude
+            && re.is_match(&word)
+        {
+            return None;
+        }
+        Some(word)
+    }
+
+    fn normalize_word<'a>(&self, token: &'a str) -> Option<Cow<'a, str>> {
+        match self.numbers {
+            Numbers::Keep => {}
+            Numbers::Drop => {
+                if token.chars().all(|c| c.is_ascii_digit()) {
+                    return None;
+                }
+            }
+            Numbers::Mask => {
+                if self.unit == Unit::Word
+                    && let Some(class) = number_class(token)
+                {
+                    return Some(Cow::Borrowed(class));
+                }
+            }
+        }
+
+        // Filtre de longueur minimale
+        if let Some(min) = self.min_length
+            && token.chars().count() < min
+        {
+            return None;
+        }
+
+        if let Some(lang) = self.stopwords
+            && lang.is_stopword(&lowercase(token))
+        {
+            return None;
+        }
+
+        if self.ignore_case {
+            Some(lowercase(token))
+        } else {
+            Some(Cow::Borrowed(token))
+        }
+    }
+
+    /// Les mots à compter : tokens filtrés et normalisés
+    pub fn words<'a>(&'a self, text: &'a str) -> impl Iterator<Item = Cow<'a, str>> + 'a {
+        self.tokens(text).filter_map(|t| self.normalize(t.text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(tok: &Tokenizer, text: &str) -> Vec<String> {
+        tok.words(text).map(|w| w.into_owned()).collect()
+    }
+
+    #[test]
+    fn word_unit_keeps_quoted_tokens() {
+        let tok = Tokenizer::new();
+        assert_eq!(
+            words(&tok, r#"say "Hello World" and 'bye' now"#),
+            vec!["say", "\"Hello World\"", "and", "'bye'", "now"]
+        );
+    }
+
+    #[test]
+    fn scanner_matches_regex() {
+        let text = "Ça va? l'été \"quoted text\" \"\" 'x' don't x²  ½ \
+                    naïve e\u{301}t\u{e9} ٣٤ foo_bar\u{2040}baz a\u{200d}b 日本語 \"open";
+        let re = Regex::new(WORD_PATTERN).unwrap();
+        let expected: Vec<(usize, &str)> = re
+            .find_iter(text)
+            .map(|m| (m.start(), m.as_str()))
+            .collect();
+        let scanned: Vec<(usize, &str)> = Scanner { text, pos: 0 }
+            .map(|t| (t.start, t.text))
+            .collect();
+        assert_eq!(scanned, expected);
+        assert_eq!(lowercase("déjà"), "déjà");
+        assert!(matches!(lowercase("abc"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn char_and_grapheme_units() {
+        // "e" + accent combinant : deux caractères, un seul graphème
+        let text = "Ae\u{301} a";
+        let chars = Tokenizer::new().unit(Unit::Char).ignore_case(true);
+        assert_eq!(words(&chars, text), vec!["a", "e", "\u{301}", "a"]);
+
+        let graphemes = Tokenizer::new().unit(Unit::Grapheme);
+        assert_eq!(words(&graphemes, text), vec!["A", "e\u{301}", "a"]);
+    }
+
+    #[test]
+    fn stopwords_are_dropped_in_any_case() {
+        let tok = Tokenizer::new().stopwords(crate::language::by_code("en"));
+        assert_eq!(words(&tok, "The cat and THE hat"), vec!["cat", "hat"]);
+    }
+
+    #[test]
+    fn number_filters() {
+        let text = "id 42 took 3.14s req 550e8400-e29b-41d4-a716-446655440000 at a3f9c2 0x1F";
+        let drop = Tokenizer::new().numbers(Numbers::Drop);
+        assert_eq!(words(&drop, "id 42 took 3s 2024"), vec!["id", "took", "3s"]);
+
+        let mask = Tokenizer::new().numbers(Numbers::Mask);
+        assert_eq!(
+            words(&mask, text),
+            vec![
+                "id", "<NUM>", "took", "<NUM>", "14s", "req", "<UUID>", "at", "<HEX>", "<HEX>"
+            ]
+        );
+        assert_eq!(words(&mask, "1,000 deadbeef"), vec!["<NUM>", "deadbeef"]);
+    }
+
+    #[test]
+    fn match_and_exclude() {
+        let tok = Tokenizer::new()
+            .ignore_case(true)
+            .matching(Some(Regex::new("^e").unwrap()))
+            .excluding(Some(Regex::new("s$").unwrap()));
+        assert_eq!(
+            words(&tok, "Error errors warn ERROR"),
+            vec!["error", "error"]
+        );
+    }
+
+    #[test]
+    fn code_identifiers() {
+        let tok = Tokenizer::new()
+            .ignore_case(true)
+            .code(Some(CodeMode::Split))
+            .code_lang(Some(CodeLang::Rust));
+        assert_eq!(
+            words(&tok, "fn parseHttpRequest(max_len: usize) -> Self { 42 }"),
+            vec!["parse", "http", "request", "max", "len", "usize"]
+        );
+
+        let both = Tokenizer::new()
+            .code(Some(CodeMode::Both))
+            .code_lang(Some(CodeLang::Rust));
+        let tokens: Vec<Token> = both.tokens("let getName = 1").collect();
+        assert_eq!(
+            tokens.iter().map(|t| t.text).collect::<Vec<_>>(),
+            vec!["getName", "get", "Name"]
+        );
+        assert_eq!((tokens[2].start, tokens[2].end), (7, 11));
+    }
+
+    #[test]
+    fn byte_keys() {
+        assert_eq!(byte_key(b'A'), "0x41 'A'");
+        assert_eq!(byte_key(0xc3), "0xc3");
+        assert_eq!(byte_key(b' '), "0x20");
+    }
+}
@@ -0,0 +1,474 @@
+//! Regroupement des variantes d'orthographe (`--group-variants`) : fautes de
+//! frappe par distance d'édition, ou mots qui se prononcent pareil (Soundex,
+//! Metaphone). Travaille sur les compteurs finaux, donc sur le vocabulaire.
+
+use crate::counter::WordCounter;
+use crate::locale::Collator;
+use crate::spell::edit_distance;
+use std::cmp::Reverse;
+use std::collections::hash_map::RandomState;
+use std::collections::{HashMap, HashSet};
+use std::hash::BuildHasher;
+use std::str::FromStr;
+
+/// Critère de regroupement
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Au plus N éditions du mot le plus fréquent du groupe
+    Edit(usize),
+    Soundex,
+    Metaphone,
+}
+
+impl GroupBy {
+    pub fn name(self) -> String {
+        match self {
+            GroupBy::Edit(n) => format!("edit:{n}"),
+            GroupBy::Soundex => "soundex".to_string(),
+            GroupBy::Metaphone => "metaphone".to_string(),
+        }
+    }
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "soundex" => Ok(GroupBy::Soundex),
+            "metaphone" => Ok(GroupBy::Metaphone),
+            _ => match s.strip_prefix("edit:").map(str::parse) {
+                Some(Ok(n @ 1..=3)) => Ok(GroupBy::Edit(n)),
+                _ => Err(format!(
+                    "unknown grouping '{s}' (expected edit:1, edit:2, edit:3, soundex or metaphone)"
+                )),
+            },
+        }
+    }
+}
+
+/// Un groupe de variantes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cluster<'a> {
+    /// somme des fréquences des membres
+    pub total: usize,
+    /// (mot, fréquence), le plus fréquent d'abord : c'est le nom du groupe
+    pub members: Vec<(&'a str, usize)>,
+}
+
+impl<'a> Cluster<'a> {
+    pub fn name(&self) -> &'a str {
+        self.members[0].0
+    }
+}
+
+/// Regroupe les mots de `counter`. Les groupes sont triés par fréquence
+/// totale ; un mot sans variante forme un groupe à lui seul.
+pub fn group<'a>(counter: &'a WordCounter, by: GroupBy, collator: &Collator) -> Vec<Cluster<'a>> {
+    let ranked = counter.ranked(collator);
+
+    // indice du groupe de chaque mot, dans l'ordre de `ranked`
+    let assignment: Vec<usize> = match by {
+        GroupBy::Edit(n) => edit_groups(&ranked, n),
+        GroupBy::Soundex => key_groups(&ranked, soundex),
+        GroupBy::Metaphone => key_groups(&ranked, metaphone),
+    };
+
+    let mut clusters: Vec<Cluster> = Vec::new();
+    for (&(word, count), &g) in ranked.iter().zip(&assignment) {
+        if g == clusters.len() {
+            clusters.push(Cluster {
+                total: 0,
+                members: Vec::new(),
+            });
+        }
+        clusters[g].total += count;
+        clusters[g].members.push((word, count));
+    }
+
+    clusters.sort_by_cached_key(|c| (Reverse(c.total), collator.sort_key(c.name()), c.name()));
+    clusters
+}
+
+/// Groupes par clé phonétique ; les mots sans clé (nombres...) restent seuls.
+/// Les numéros de groupe sont attribués dans l'ordre de première apparition.
+fn key_groups(ranked: &[(&str, usize)], key: fn(&str) -> String) -> Vec<usize> {
+    let mut by_key: HashMap<String, usize> = HashMap::new();
+    let mut next = 0;
+    ranked
+        .iter()
+        .map(|(w, _)| {
+            let k = key(w);
+            let id = if k.is_empty() {
+                None
+            } else {
+                by_key.get(&k).copied()
+            };
+            id.unwrap_or_else(|| {
+                if !k.is_empty() {
+                    by_key.insert(k, next);
+                }
+                next += 1;
+                next - 1
+            })
+        })
+        .collect()
+}
+
+/// Du plus fréquent au moins fréquent, chaque mot rejoint le groupe d'un
+/// représentant à au plus `max` éditions, ou en fonde un nouveau. Les
+/// candidats sont trouvés par suppressions communes (principe de SymSpell)
+/// plutôt qu'en comparant toutes les paires. Les mots trop courts (moins de
+/// 3×max+1 lettres) ne sont pas regroupés : "cat" et "car" sont deux mots.
+///
+/// Seuls les représentants entrent dans l'index, et chaque variante n'y
+/// coûte qu'un hachage et deux numéros (environ 20 octets) : les lettres
+/// sont gardées une fois par représentant, pas une fois par variante.
+fn edit_groups(ranked: &[(&str, usize)], max: usize) -> Vec<usize> {
+    const NONE: u32 = u32::MAX;
+    let min_len = 3 * max + 1;
+    let hasher = RandomState::new();
+    // hachage d'une variante par suppression -> premier maillon de la liste
+    // des représentants qui la produisent ; maillon = (représentant, suivant)
+    let mut index: HashMap<u64, u32> = HashMap::new();
+    let mut links: Vec<(u32, u32)> = Vec::new();
+    // représentants : (indice de groupe, lettres)
+    let mut reps: Vec<(usize, Vec<char>)> = Vec::new();
+    let mut next = 0;
+
+    ranked
+        .iter()
+        .map(|(w, _)| {
+            let chars: Vec<char> = w.to_lowercase().chars().collect();
+            let id = next;
+            next += 1;
+            if chars.len() < min_len || !chars.iter().all(|c| c.is_alphabetic()) {
+                return id;
+            }
+
+            let variants = deletions(&chars, max, &hasher);
+            // une collision de hachage ne fait qu'ajouter un candidat, que
+            // la distance d'édition écarte
+            let found = variants
+                .iter()
+                .filter_map(|v| index.get(v))
+                .flat_map(|&head| {
+                    std::iter::successors(Some(head), |&l| {
+                        let next = links[l as usize].1;
+                        (next != NONE).then_some(next)
+                    })
+                    .map(|l| links[l as usize].0)
+                })
+                .filter_map(|r| {
+                    let (g, rep) = &reps[r as usize];
+                    edit_distance(&chars, rep, max).map(|d| (d, *g))
+                })
+                .min();
+            if let Some((_, g)) = found {
+                next -= 1;
+                return g;
+            }
+
+            let r = u32::try_from(reps.len()).expect("too many representatives");
+            reps.push((id, chars));
+            for v in variants {
+                let link = u32::try_from(links.len()).expect("too many variants");
+                let head = index.insert(v, link).unwrap_or(NONE);
+                links.push((r, head));
+            }
+            id
+        })
+        .collect()
+}
+
+/// Hachages du mot et de toutes ses variantes obtenues en supprimant jusqu'à
+/// `max` lettres, sans doublon
+fn deletions(chars: &[char], max: usize, hasher: &RandomState) -> Vec<u64> {
+    let mut out = vec![hasher.hash_one(chars)];
+    let mut frontier = vec![chars.to_vec()];
+    for _ in 0..max {
+        let mut next = HashSet::new();
+        for w in &frontier {
+            for i in 0..w.len() {
+                let mut v = w.clone();
+                v.remove(i);
+                next.insert(v);
+            }
+        }
+        out.extend(next.iter().map(|v| hasher.hash_one(v)));
+        frontier = next.into_iter().collect();
+    }
+    out.sort_unstable();
+    out.dedup();
+    out
+}
+
+/// Lettres ASCII majuscules du mot (accents retirés), sans le reste
+fn ascii_letters(word: &str) -> Vec<char> {
+    use unicode_normalization::UnicodeNormalization;
+    word.nfd()
+        .filter(char::is_ascii_alphabetic)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// Code Soundex américain ("Robert" → R163), vide si le mot n'a pas de lettre
+pub fn soundex(word: &str) -> String {
+    let letters = ascii_letters(word);
+    let Some(&first) = letters.first() else {
+        return String::new();
+    };
+    let digit = |c: char| match c {
+        'B' | 'F' | 'P' | 'V' => '1',
+        'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => '2',
+        'D' | 'T' => '3',
+        'L' => '4',
+        'M' | 'N' => '5',
+        'R' => '6',
+        // H et W ne séparent pas deux consonnes de même code
+        'H' | 'W' => '-',
+        _ => '0',
+    };
+
+    let mut code = String::from(first);
+    let mut last = digit(first);
+    for &c in &letters[1..] {
+        let d = digit(c);
+        if d == '-' {
+            continue;
+        }
+        if d != '0' && d != last {
+            code.push(d);
+            if code.len() == 4 {
+                break;
+            }
+        }
+        last = d;
+    }
+    while code.len() < 4 {
+        code.push('0');
+    }
+    code
+}
+
+/// Code Metaphone (Lawrence Philips, 1990) : "thumb" → 0M, "knight" → NT
+pub fn metaphone(word: &str) -> String {
+    let mut w = ascii_letters(word);
+    // lettres doublées réduites, sauf C ("accent" garde ses deux sons)
+    w.dedup_by(|a, b| a == b && *a != 'C');
+    if w.is_empty() {
+        return String::new();
+    }
+
+    let is_vowel = |c: char| matches!(c, 'A' | 'E' | 'I' | 'O' | 'U');
+    let at = |i: usize| w.get(i).copied().unwrap_or('\0');
+    let mut out = String::new();
+
+    // débuts particuliers
+    let mut i = 0;
+    match (at(0), at(1)) {
+        ('A', 'E') | ('G', 'N') | ('K', 'N') | ('P', 'N') | ('W', 'R') => i = 1,
+        ('X', _) => {
+            out.push('S');
+            i = 1;
+        }
+        ('W', 'H') => {
+            out.push('W');
+            i = 2;
+        }
+        _ => {}
+    }
+
+    while i < w.len() {
+        let c = w[i];
+        let (prev, next, next2) = (if i > 0 { at(i - 1) } else { '\0' }, at(i + 1), at(i + 2));
+        match c {
+            'A' | 'E' | 'I' | 'O' | 'U' => {
+                if i == 0 {
+                    out.push(c);
+                }
+            }
+            'B' => {
+                if !(prev == 'M' && i + 1 == w.len()) {
+                    out.push('B');
+                }
+            }
+            'C' => {
+                if next == 'I' && next2 == 'A' {
+                    out.push('X');
+                } else if next == 'H' {
+                    out.push(if prev == 'S' { 'K' } else { 'X' });
+                    i += 1;
+                } else if matches!(next, 'I' | 'E' | 'Y') {
+                    if prev != 'S' {
+                        out.push('S');
+                    }
+                } else {
+                    out.push('K');
+                }
+            }
+            'D' => {
+                if next == 'G' && matches!(next2, 'E' | 'Y' | 'I') {
+                    out.push('J');
+                    i += 1;
+                } else {
+                    out.push('T');
+                }
+            }
+            'G' => {
+                let silent_gh = next == 'H' && i + 2 < w.len() && !is_vowel(next2);
+                let silent_gn =
+                    next == 'N' && (i + 2 == w.len() || (next2 == 'E' && at(i + 3) == 'D'));
+                if silent_gh || silent_gn {
+                    // muet
+                } else if matches!(next, 'I' | 'E' | 'Y') && prev != 'G' {
+                    out.push('J');
+                } else if next == 'H' {
+                    // "-gh" final ou devant voyelle : "tough", "ghost"
+                    out.push(if i == 0 { 'K' } else { 'F' });
+                    i += 1;
+                } else {
+                    out.push('K');
+                }
+            }
+            'H' => {
+                let after_modifier = matches!(prev, 'C' | 'S' | 'P' | 'T' | 'G');
+                // muet après une voyelle s'il n'est pas suivi d'une voyelle
+                if !after_modifier && (!is_vowel(prev) || is_vowel(next)) {
+                    out.push('H');
+                }
+            }
+            'K' => {
+                if prev != 'C' {
+                    out.push('K');
+                }
+            }
+            'P' => {
+                if next == 'H' {
+                    out.push('F');
+                    i += 1;
+                } else {
+                    out.push('P');
+                }
+            }
+            'Q' => out.push('K'),
+            'S' => {
+                if next == 'H' {
+                    out.push('X');
+                    i += 1;
+                } else if next == 'I' && matches!(next2, 'O' | 'A') {
+                    out.push('X');
+                } else {
+                    out.push('S');
+                }
+            }
+            'T' => {
+                if next == 'I' && matches!(next2, 'O' | 'A') {
+                    out.push('X');
+                } else if next == 'H' {
+                    out.push('0');
+                    i += 1;
+                } else if !(next == 'C' && next2 == 'H') {
+                    out.push('T');
+                }
+            }
+            'V' => out.push('F'),
+            'W' | 'Y' => {
+                if is_vowel(next) {
+                    out.push(c);
+                }
+            }
+            'X' => out.push_str("KS"),
+            'Z' => out.push('S'),
+            _ => out.push(c),
+        }
+        i += 1;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soundex_codes() {
+        assert_eq!(soundex("Robert"), "R163");
+        assert_eq!(soundex("Rupert"), "R163");
+        assert_eq!(soundex("Ashcraft"), "A261");
+        assert_eq!(soundex("Tymczak"), "T522");
+        assert_eq!(soundex("Pfister"), "P236");
+        assert_eq!(soundex("Lee"), "L000");
+        assert_eq!(soundex("42"), "");
+    }
+
+    #[test]
+    fn metaphone_codes() {
+        assert_eq!(metaphone("thumb"), "0M");
+        assert_eq!(metaphone("knight"), "NT");
+        assert_eq!(metaphone("phone"), "FN");
+        assert_eq!(metaphone("fone"), "FN");
+        assert_eq!(metaphone("school"), "SKL");
+        assert_eq!(metaphone("Smith"), metaphone("Smyth"));
+        assert_eq!(metaphone("colour"), metaphone("color"));
+    }
+
+    #[test]
+    fn edit_clusters_follow_the_most_frequent_word() {
+        let mut counter = WordCounter::new();
+        counter.feed("color color color colour colour recieve receive receive cat car");
+
+        let clusters = group(&counter, GroupBy::Edit(1), &Collator::default());
+        let summary: Vec<(&str, usize, usize)> = clusters
+            .iter()
+            .map(|c| (c.name(), c.total, c.members.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("color", 5, 2),
+                ("receive", 3, 2),
+                ("car", 1, 1),
+                ("cat", 1, 1)
+            ]
+        );
+        assert_eq!(clusters[0].members, vec![("color", 3), ("colour", 2)]);
+    }
+
+    #[test]
+    fn representatives_sharing_a_variant_stay_apart() {
+        // "defghijkl" est une variante des deux premiers mots, à 6 éditions
+        // l'un de l'autre
+        let mut counter = WordCounter::new();
+        counter
+            .feed("abcdefghijkl abcdefghijkl abcdefghijkl defghijklxyz defghijklxyz defghijklxyw");
+
+        let clusters = group(&counter, GroupBy::Edit(3), &Collator::default());
+        let summary: Vec<(&str, usize, usize)> = clusters
+            .iter()
+            .map(|c| (c.name(), c.total, c.members.len()))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("abcdefghijkl", 3, 1), ("defghijklxyz", 3, 2)]
+        );
+    }
+
+    #[test]
+    fn phonetic_clusters() {
+        let mut counter = WordCounter::new();
+        counter.feed("Smith Smyth Smith Schmidt Jones");
+
+        let clusters = group(&counter, GroupBy::Soundex, &Collator::default());
+        assert_eq!(clusters[0].name(), "Smith");
+        assert_eq!(clusters[0].total, 4);
+    }
+
+    #[test]
+    fn parse_grouping() {
+        assert_eq!("edit:2".parse(), Ok(GroupBy::Edit(2)));
+        assert_eq!("metaphone".parse(), Ok(GroupBy::Metaphone));
+        assert!("edit:0".parse::<GroupBy>().is_err());
+        assert!("edit".parse::<GroupBy>().is_err());
+    }
+}
@@ -0,0 +1,273 @@
+//! Nuage de mots en SVG (`--wordcloud`) : placement en spirale autour du
+//! centre avec détection des collisions, couleurs tirées d'une palette.
+//!
+//! Le hasard (angle de départ, couleurs) vient d'un générateur déterministe
+//! initialisé par `seed` : même graine, même fichier.
+
+use std::fmt::Write as _;
+
+/// Palettes prédéfinies pour `--palette`
+pub const PALETTES: &[(&str, &[&str])] = &[
+    (
+        "category10",
+        &[
+            "#1f77b4", "#ff7f0e", "#2ca02c", "#d62728", "#9467bd", "#8c564b", "#e377c2", "#7f7f7f",
+            "#bcbd22", "#17becf",
+        ],
+    ),
+    (
+        "viridis",
+        &["#440154", "#3b528b", "#21918c", "#5ec962", "#fde725"],
+    ),
+    (
+        "warm",
+        &["#7f0000", "#b30000", "#d7301f", "#ef6548", "#fc8d59"],
+    ),
+    ("grey", &["#222222", "#555555", "#888888"]),
+];
+
+/// Une palette par nom, ou une liste de couleurs séparées par des virgules
+/// ("#336699,#cc3300,teal")
+pub fn parse_palette(s: &str) -> Result<Vec<String>, String> {
+    if let Some((_, colors)) = PALETTES.iter().find(|(name, _)| *name == s) {
+        return Ok(colors.iter().map(|c| c.to_string()).collect());
+    }
+
+    let colors: Vec<String> = s.split(',').map(|c| c.trim().to_string()).collect();
+    let valid = |c: &str| {
+        let hex = c
+            .strip_prefix('#')
+            .is_some_and(|h| matches!(h.len(), 3 | 6) && h.chars().all(|d| d.is_ascii_hexdigit()));
+        let named = !c.is_empty() && c.chars().all(|d| d.is_ascii_alphabetic());
+        hex || named
+    };
+    match colors.iter().find(|c| !valid(c)) {
+        None => Ok(colors),
+        Some(bad) => {
+            let names: Vec<&str> = PALETTES.iter().map(|(n, _)| *n).collect();
+            Err(format!(
+                "invalid color '{bad}' (expected {} or a comma-separated list of #rrggbb colors)",
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+/// Générateur pseudo-aléatoire SplitMix64 : petit, rapide et reproductible
+#[derive(Debug, Clone)]
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Nombre dans [0, 1)
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub width: f64,
+    pub height: f64,
+    pub min_font: f64,
+    pub max_font: f64,
+    pub palette: Vec<String>,
+    pub seed: u64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            width: 800.0,
+            height: 600.0,
+            min_font: 12.0,
+            max_font: 72.0,
+            palette: parse_palette("category10").expect("built-in palette"),
+            seed: 42,
+        }
+    }
+}
+
+/// Un mot placé : (x, y) est le coin haut gauche de sa boîte
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placed<'a> {
+    pub word: &'a str,
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+    pub font_size: f64,
+    pub color: &'a str,
+}
+
+impl Placed<'_> {
+    fn overlaps(&self, x: f64, y: f64, w: f64, h: f64) -> bool {
+        x < self.x + self.width && self.x < x + w && y < self.y + self.height && self.y < y + h
+    }
+}
+
+/// Largeur approximative d'un texte : pas de métrique de police ici, on
+/// compte ~0,6 em par caractère (un peu large, pour éviter les chevauchements)
+fn text_width(word: &str, font_size: f64) -> f64 {
+    word.chars().count() as f64 * font_size * 0.6
+}
+
+/// Place les mots (triés du plus au moins fréquent) ; ceux qui ne tiennent
+/// plus dans le cadre sont omis
+pub fn layout<'a>(words: &[(&'a str, usize)], opts: &'a Options) -> Vec<Placed<'a>> {
+    let mut rng = Rng(opts.seed);
+    let max = words.iter().map(|w| w.1).max().unwrap_or(1) as f64;
+    let min = words.iter().map(|w| w.1).min().unwrap_or(1) as f64;
+    let mut placed: Vec<Placed> = Vec::new();
+
+    for &(word, count) in words {
+        // taille proportionnelle à la racine de la fréquence, pour que les
+        // premiers mots n'écrasent pas tout le reste
+        let t = if max > min {
+            ((count as f64).sqrt() - min.sqrt()) / (max.sqrt() - min.sqrt())
+        } else {
+            1.0
+        };
+        let font_size = opts.min_font + t * (opts.max_font - opts.min_font);
+        let (w, h) = (text_width(word, font_size), font_size);
+        let color = opts
+            .palette
+            .get(rng.next_u64() as usize % opts.palette.len().max(1))
+            .map_or("black", String::as_str);
+
+        // spirale d'Archimède depuis le centre, angle de départ aléatoire
+        let start = rng.next_f64() * std::f64::consts::TAU;
+        let (cx, cy) = (opts.width / 2.0, opts.height / 2.0);
+        let mut angle = 0.0f64;
+        let max_radius = opts.width.hypot(opts.height) / 2.0;
+
+        loop {
+            let r = 2.0 * angle;
+            if r > max_radius {
+                break;
+            }
+            let x = cx + r * (start + angle).cos() - w / 2.0;
+            let y = cy + r * (start + angle).sin() * (opts.height / opts.width) - h / 2.0;
+            let inside = x >= 0.0 && y >= 0.0 && x + w <= opts.width && y + h <= opts.height;
+            if inside && !placed.iter().any(|p| p.overlaps(x, y, w, h)) {
+                placed.push(Placed {
+                    word,
+                    x,
+                    y,
+                    width: w,
+                    height: h,
+                    font_size,
+                    color,
+                });
+                break;
+            }
+            angle += 0.1;
+        }
+    }
+
+    placed
+}
+
+/// Le document SVG d'un nuage déjà placé
+pub fn to_svg(placed: &[Placed], opts: &Options) -> String {
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+        w = opts.width,
+        h = opts.height
+    );
+    let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+    for p in placed {
+        // la ligne de base est à ~80 % de la hauteur de la boîte
+        let _ = writeln!(
+            svg,
+            r#"<text x="{:.1}" y="{:.1}" font-family="sans-serif" font-size="{:.1}" fill="{}">{}</text>"#,
+            p.x,
+            p.y + p.height * 0.8,
+            p.font_size,
+            p.color,
+            xml_escape(p.word)
+        );
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words() -> Vec<(&'static str, usize)> {
+        vec![
+            ("rust", 50),
+            ("cargo", 30),
+            ("crate", 20),
+            ("<trait>", 10),
+            ("borrow", 5),
+            ("lifetime", 1),
+        ]
+    }
+
+    #[test]
+    fn words_do_not_overlap_and_stay_inside() {
+        let opts = Options::default();
+        let placed = layout(&words(), &opts);
+
+        assert_eq!(placed.len(), 6);
+        assert_eq!(placed[0].font_size, opts.max_font);
+        assert_eq!(placed[5].font_size, opts.min_font);
+        for (i, a) in placed.iter().enumerate() {
+            assert!(a.x >= 0.0 && a.x + a.width <= opts.width);
+            assert!(a.y >= 0.0 && a.y + a.height <= opts.height);
+            for b in &placed[i + 1..] {
+                assert!(
+                    !a.overlaps(b.x, b.y, b.width, b.height),
+                    "{} / {}",
+                    a.word,
+                    b.word
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_svg() {
+        let opts = Options::default();
+        let a = to_svg(&layout(&words(), &opts), &opts);
+        let b = to_svg(&layout(&words(), &opts), &opts);
+        assert_eq!(a, b);
+        assert!(a.contains("&lt;trait&gt;"));
+
+        let other = Options {
+            seed: 7,
+            ..Options::default()
+        };
+        assert_ne!(a, to_svg(&layout(&words(), &other), &other));
+    }
+
+    #[test]
+    fn palettes() {
+        assert_eq!(parse_palette("grey").unwrap().len(), 3);
+        assert_eq!(
+            parse_palette("#336699, teal").unwrap(),
+            vec!["#336699", "teal"]
+        );
+        assert!(parse_palette("#12345").is_err());
+        assert!(parse_palette("red;blue").is_err());
+    }
+}
@@ -1,7 +1,8 @@
 use clap::{ArgGroup, Parser};
 use std::fs::OpenOptions;
-use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
 use std::path::PathBuf;
+use std::str::FromStr;
 
 /// Read and write binary files in hexadecimal
 #[derive(Parser, Debug)]
@@ -25,19 +26,310 @@ struct Args {
     #[arg(short, long)]
     write: Option<String>,
 
-    /// Offset in bytes (decimal or 0x hex)
-    #[arg(short, long, default_value = "0")]
+    /// Offset in bytes (decimal or 0x hex); negative counts from the end of the file
+    #[arg(short, long, default_value = "0", allow_hyphen_values = true)]
     offset: String,
 
-    /// Number of bytes to read
+    /// Number of bytes to read (decimal or 0x hex), or "all" for the rest of the file
     #[arg(short, long, default_value = "16")]
-    size: usize,
+    size: String,
+
+    /// Byte range to read instead of --offset/--size: START..END (END excluded),
+    /// START.. or START+LEN; START and END may be negative
+    #[arg(long, allow_hyphen_values = true, conflicts_with_all = ["offset", "size", "write"])]
+    range: Option<String>,
+
+    /// Output preset: xxd (like `xxd`) or hexdump (like `hexdump -C`); the layout flags below override it
+    #[arg(long)]
+    preset: Option<Preset>,
+
+    /// Bytes per line
+    #[arg(long)]
+    cols: Option<usize>,
+
+    /// Bytes per group: 1, 2, 4 or 8, shown as one number
+    #[arg(long)]
+    group: Option<usize>,
+
+    /// Byte order of grouped words: little or big
+    #[arg(long)]
+    endian: Option<Endian>,
+
+    /// Uppercase hex digits in the byte columns
+    #[arg(long)]
+    upper: bool,
+
+    /// Byte display: hex, dec, oct or bin
+    #[arg(long)]
+    base: Option<Base>,
+
+    /// Minimum number of hex digits of the offset column
+    #[arg(long)]
+    offset_width: Option<usize>,
+}
+
+/// Output compatible with another dump tool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Preset {
+    Xxd,
+    Hexdump,
+}
+
+impl FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "xxd" => Ok(Preset::Xxd),
+            "hexdump" | "hexdump-c" => Ok(Preset::Hexdump),
+            _ => Err(format!("unknown preset '{s}' (expected xxd or hexdump)")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Endian {
+    Little,
+    Big,
+}
+
+impl FromStr for Endian {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "little" | "le" => Ok(Endian::Little),
+            "big" | "be" => Ok(Endian::Big),
+            _ => Err(format!("unknown byte order '{s}' (expected little or big)")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Base {
+    Hex,
+    Dec,
+    Oct,
+    Bin,
+}
+
+impl FromStr for Base {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "hex" => Ok(Base::Hex),
+            "dec" => Ok(Base::Dec),
+            "oct" => Ok(Base::Oct),
+            "bin" => Ok(Base::Bin),
+            _ => Err(format!(
+                "unknown base '{s}' (expected hex, dec, oct or bin)"
+            )),
+        }
+    }
+}
+
+/// How dump lines are laid out
+#[derive(Debug, Clone)]
+struct Layout {
+    cols: usize,
+    group: usize,
+    endian: Endian,
+    upper: bool,
+    base: Base,
+    offset_width: usize,
+    /// between the offset and the bytes
+    offset_sep: &'static str,
+    /// extra space in the middle of the line (`hexdump -C`)
+    split_half: bool,
+    /// before the ASCII column
+    ascii_sep: &'static str,
+    /// ASCII column between `|`
+    ascii_bars: bool,
+    /// repeated lines shown once, then `*` (`hexdump -C`)
+    squeeze: bool,
+    /// last line is the end offset alone (`hexdump -C`)
+    end_offset: bool,
+}
+
+impl Layout {
+    /// Layout of `preset`, or the historical hextool one
+    fn preset(preset: Option<Preset>) -> Self {
+        let default = Layout {
+            cols: 16,
+            group: 1,
+            endian: Endian::Big,
+            upper: false,
+            base: Base::Hex,
+            offset_width: 8,
+            offset_sep: ": ",
+            split_half: false,
+            ascii_sep: "",
+            ascii_bars: true,
+            squeeze: false,
+            end_offset: false,
+        };
+        match preset {
+            None => default,
+            Some(Preset::Xxd) => Layout {
+                group: 2,
+                ascii_sep: " ",
+                ascii_bars: false,
+                ..default
+            },
+            Some(Preset::Hexdump) => Layout {
+                offset_sep: "  ",
+                split_half: true,
+                ascii_sep: " ",
+                squeeze: true,
+                end_offset: true,
+                ..default
+            },
+        }
+    }
+
+    /// Preset adjusted by the layout flags
+    fn from_args(args: &Args) -> Result<Self, String> {
+        let mut layout = Layout::preset(args.preset);
+        if let Some(group) = args.group {
+            if ![1, 2, 4, 8].contains(&group) {
+                return Err(format!(
+                    "invalid group size {group} (expected 1, 2, 4 or 8)"
+                ));
+            }
+            layout.group = group;
+        }
+        if let Some(cols) = args.cols {
+            layout.cols = cols;
+        }
+        if !(1..=256).contains(&layout.cols) || !layout.cols.is_multiple_of(layout.group) {
+            return Err(format!(
+                "invalid column count {} (expected 1 to 256, a multiple of the group size {})",
+                layout.cols, layout.group
+            ));
+        }
+        if let Some(endian) = args.endian {
+            layout.endian = endian;
+        }
+        if let Some(base) = args.base {
+            layout.base = base;
+        }
+        if let Some(width) = args.offset_width {
+            layout.offset_width = width;
+        }
+        layout.upper |= args.upper;
+        // le séparateur du milieu n'a de sens que pour un nombre pair de groupes
+        layout.split_half &= (layout.cols / layout.group).is_multiple_of(2);
+        Ok(layout)
+    }
+
+    /// Digits needed to show a group of `bytes` bytes
+    fn digits(&self, bytes: usize) -> usize {
+        let bits = 8 * bytes as u32;
+        match self.base {
+            Base::Hex => 2 * bytes,
+            Base::Bin => bits as usize,
+            Base::Oct => bits.div_ceil(3) as usize,
+            Base::Dec => {
+                let max = if bits == 64 {
+                    u64::MAX
+                } else {
+                    (1u64 << bits) - 1
+                };
+                max.to_string().len()
+            }
+        }
+    }
+
+    /// Offset column, always lowercase (as `xxd -u` does)
+    fn offset(&self, offset: u64) -> String {
+        format!("{:0w$x}", offset, w = self.offset_width)
+    }
+
+    /// A group of (up to `self.group`) bytes as one number, padded to the
+    /// width of a full group
+    fn group_text(&self, bytes: &[u8]) -> String {
+        let value = match self.endian {
+            Endian::Big => bytes.iter().fold(0u64, |v, &b| v << 8 | b as u64),
+            Endian::Little => bytes.iter().rev().fold(0u64, |v, &b| v << 8 | b as u64),
+        };
+        let w = self.digits(bytes.len());
+        let text = match (self.base, self.upper) {
+            (Base::Hex, false) => format!("{:0w$x}", value),
+            (Base::Hex, true) => format!("{:0w$X}", value),
+            (Base::Dec, _) => format!("{:0w$}", value),
+            (Base::Oct, _) => format!("{:0w$o}", value),
+            (Base::Bin, _) => format!("{:0w$b}", value),
+        };
+        // groupe incomplet en fin de fichier : aligné comme le ferait xxd
+        let full = self.digits(self.group);
+        match self.endian {
+            Endian::Big => format!("{:<full$}", text),
+            Endian::Little => format!("{:>full$}", text),
+        }
+    }
+
+    /// One dump line: offset, groups, ASCII
+    fn write_line(&self, out: &mut impl Write, offset: u64, chunk: &[u8]) -> io::Result<()> {
+        write!(out, "{}{}", self.offset(offset), self.offset_sep)?;
+
+        let groups = self.cols / self.group;
+        for g in 0..groups {
+            if self.split_half && g == groups / 2 {
+                write!(out, " ")?;
+            }
+            let start = g * self.group;
+            if start < chunk.len() {
+                let end = (start + self.group).min(chunk.len());
+                write!(out, "{} ", self.group_text(&chunk[start..end]))?;
+            } else {
+                // padding for incomplete last line
+                write!(out, "{:w$} ", "", w = self.digits(self.group))?;
+            }
+        }
+
+        // ASCII representation
+        let ascii = bytes_to_ascii(chunk);
+        if self.ascii_bars {
+            writeln!(out, "{}|{}|", self.ascii_sep, ascii)
+        } else {
+            writeln!(out, "{}{}", self.ascii_sep, ascii)
+        }
+    }
+}
+
+/// Position in the file: from the start, or from the end (negative offset)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Position {
+    Start(u64),
+    End(u64),
+}
+
+impl Position {
+    /// Absolute offset in a file of `len` bytes
+    fn resolve(self, len: u64) -> Result<u64, String> {
+        match self {
+            Position::Start(o) => Ok(o),
+            Position::End(o) => len.checked_sub(o).ok_or_else(|| {
+                format!("offset -{o} is before the start of the file ({len} bytes)")
+            }),
+        }
+    }
+}
+
+/// How much to read from the start position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Extent {
+    Bytes(u64),
+    Until(Position),
+    /// Up to the end of the file
+    All,
 }
 
 fn main() -> io::Result<()> {
     let args = Args::parse();
 
-    let offset = match parse_offset(&args.offset) {
+    let offset = match parse_position(&args.offset) {
         Ok(o) => o,
         Err(e) => {
             eprintln!("Invalid offset '{}': {}", args.offset, e);
@@ -46,10 +338,41 @@ fn main() -> io::Result<()> {
     };
 
     if args.read {
-        read_mode(&args.file, offset, args.size)?;
+        let layout = Layout::from_args(&args).unwrap_or_else(|e| {
+            eprintln!("Invalid layout: {}", e);
+            std::process::exit(1);
+        });
+        let (start, extent) = match &args.range {
+            Some(r) => parse_range(r).unwrap_or_else(|e| {
+                eprintln!("Invalid range '{}': {}", r, e);
+                std::process::exit(1);
+            }),
+            None => (
+                offset,
+                parse_size(&args.size).unwrap_or_else(|e| {
+                    eprintln!("Invalid size '{}': {}", args.size, e);
+                    std::process::exit(1);
+                }),
+            ),
+        };
+        match read_mode(&args.file, start, extent, &layout) {
+            // sortie fermée en cours de route (`| head`) : ce n'est pas une erreur
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {}
+            Err(e) if e.kind() == io::ErrorKind::InvalidInput => {
+                eprintln!("Invalid range: {}", e);
+                std::process::exit(1);
+            }
+            r => r?,
+        }
     } else if let Some(hex_str) = args.write {
         match hex_to_bytes(&hex_str) {
-            Ok(bytes) => write_mode(&args.file, offset, &bytes)?,
+            Ok(bytes) => match write_mode(&args.file, offset, &bytes) {
+                Err(e) if e.kind() == io::ErrorKind::InvalidInput => {
+                    eprintln!("Invalid offset '{}': {}", args.offset, e);
+                    std::process::exit(1);
+                }
+                r => r?,
+            },
             Err(e) => {
                 eprintln!("Invalid hex string '{}': {}", hex_str, e);
                 std::process::exit(1);
@@ -69,6 +392,44 @@ fn parse_offset(s: &str) -> Result<u64, String> {
     }
 }
 
+/// Parse a position: an offset, or "-N" for N bytes before the end of the file
+fn parse_position(s: &str) -> Result<Position, String> {
+    match s.strip_prefix('-') {
+        Some(rest) => parse_offset(rest).map(Position::End),
+        None => parse_offset(s).map(Position::Start),
+    }
+}
+
+/// Parse --size: a byte count or "all"
+fn parse_size(s: &str) -> Result<Extent, String> {
+    if s == "all" {
+        Ok(Extent::All)
+    } else {
+        parse_offset(s).map(Extent::Bytes)
+    }
+}
+
+/// Parse --range: "0x100..0x200", "0x100..", "-64..", "0x100+64"
+fn parse_range(s: &str) -> Result<(Position, Extent), String> {
+    if let Some((start, end)) = s.split_once("..") {
+        let start = if start.is_empty() {
+            Position::Start(0)
+        } else {
+            parse_position(start)?
+        };
+        let extent = if end.is_empty() {
+            Extent::All
+        } else {
+            Extent::Until(parse_position(end)?)
+        };
+        Ok((start, extent))
+    } else if let Some((start, len)) = s.split_once('+') {
+        Ok((parse_position(start)?, Extent::Bytes(parse_offset(len)?)))
+    } else {
+        Err("expected START..END, START.. or START+LEN".into())
+    }
+}
+
 /// Convert hex string "48656c6c6f" -> Vec<u8>
 fn hex_to_bytes(s: &str) -> Result<Vec<u8>, String> {
     // Clippy: ne plus faire `len() % 2 != 0`, utiliser is_multiple_of
@@ -96,44 +457,129 @@ fn bytes_to_ascii(bytes: &[u8]) -> String {
         .collect()
 }
 
-/// READ MODE: hex dump
-fn read_mode(path: &PathBuf, offset: u64, size: usize) -> io::Result<()> {
-    let mut file = OpenOptions::new().read(true).open(path)?;
+/// Fill `buf` as far as the input allows: a single `read` may return fewer
+/// bytes than available, so keep reading until the buffer is full or at EOF
+fn read_full(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match reader.read(&mut buf[n..]) {
+            Ok(0) => break,
+            Ok(k) => n += k,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(n)
+}
 
-    file.seek(SeekFrom::Start(offset))?;
+/// READ MODE: hex dump, streamed line by line (the file is never loaded whole)
+fn read_mode(path: &PathBuf, start: Position, extent: Extent, layout: &Layout) -> io::Result<()> {
+    let mut out = BufWriter::new(io::stdout().lock());
+    let dumped = dump(path, start, extent, layout, &mut out)?;
+    out.flush()?;
 
-    let mut buffer = vec![0u8; size];
-    let n = file.read(&mut buffer)?;
-    buffer.truncate(n);
+    // ne jamais tronquer en silence : fin de fichier avant la taille demandée
+    if let Some(wanted) = dumped.wanted
+        && dumped.read < wanted
+    {
+        eprintln!(
+            "warning: end of file reached after {} of {wanted} requested bytes (file is {} bytes)",
+            dumped.read, dumped.len
+        );
+    }
 
-    let mut current_offset = offset;
+    Ok(())
+}
+
+/// What a dump actually covered
+#[derive(Debug, PartialEq, Eq)]
+struct Dumped {
+    /// bytes dumped
+    read: u64,
+    /// bytes requested, `None` for "up to the end of the file"
+    wanted: Option<u64>,
+    /// file size
+    len: u64,
+}
 
-    for chunk in buffer.chunks(16) {
-        // offset
-        print!("{:08x}: ", current_offset);
+/// Dump the requested part of `path` to `out`
+fn dump(
+    path: &PathBuf,
+    start: Position,
+    extent: Extent,
+    layout: &Layout,
+    out: &mut impl Write,
+) -> io::Result<Dumped> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidInput, msg);
 
-        // hex bytes (16 max)
-        for i in 0..16 {
-            if i < chunk.len() {
-                print!("{:02x} ", chunk[i]);
-            } else {
-                // padding for incomplete last line
-                print!("   ");
+    let mut file = OpenOptions::new().read(true).open(path)?;
+    let len = file.metadata()?.len();
+
+    let offset = start.resolve(len).map_err(invalid)?;
+    if offset > len {
+        return Err(invalid(format!(
+            "offset 0x{offset:x} is past the end of the file ({len} bytes)"
+        )));
+    }
+    let wanted = match extent {
+        Extent::Bytes(n) => Some(n),
+        Extent::Until(end) => {
+            let end = end.resolve(len).map_err(invalid)?;
+            if end < offset {
+                return Err(invalid(format!(
+                    "range end 0x{end:x} is before its start 0x{offset:x}"
+                )));
             }
+            Some(end - offset)
         }
+        Extent::All => None,
+    };
 
-        // ASCII representation
-        let ascii = bytes_to_ascii(chunk);
-        println!("|{}|", ascii);
+    file.seek(SeekFrom::Start(offset))?;
+    let mut reader = BufReader::with_capacity(64 * 1024, file).take(wanted.unwrap_or(u64::MAX));
 
-        current_offset += chunk.len() as u64;
+    let mut line = vec![0u8; layout.cols];
+    let mut previous = Vec::new();
+    let mut squeezed = false;
+    let mut current_offset = offset;
+    loop {
+        let n = read_full(&mut reader, &mut line)?;
+        if n == 0 {
+            break;
+        }
+        let chunk = &line[..n];
+        if layout.squeeze && previous == chunk {
+            // même ligne que la précédente : une seule étoile pour toute la série
+            if !squeezed {
+                writeln!(out, "*")?;
+                squeezed = true;
+            }
+        } else {
+            layout.write_line(out, current_offset, chunk)?;
+            squeezed = false;
+            if layout.squeeze {
+                previous.clear();
+                previous.extend_from_slice(chunk);
+            }
+        }
+        current_offset += n as u64;
+        if n < line.len() {
+            break;
+        }
+    }
+    if layout.end_offset && current_offset > offset {
+        writeln!(out, "{}", layout.offset(current_offset))?;
     }
 
-    Ok(())
+    Ok(Dumped {
+        read: current_offset - offset,
+        wanted,
+        len,
+    })
 }
 
 /// WRITE MODE: write bytes at given offset and display info
-fn write_mode(path: &PathBuf, offset: u64, data: &[u8]) -> io::Result<()> {
+fn write_mode(path: &PathBuf, start: Position, data: &[u8]) -> io::Result<()> {
     let mut file = OpenOptions::new()
         .read(true)
         .write(true)
@@ -142,6 +588,10 @@ fn write_mode(path: &PathBuf, offset: u64, data: &[u8]) -> io::Result<()> {
         .truncate(false)
         .open(path)?;
 
+    let offset = start
+        .resolve(file.metadata()?.len())
+        .map_err(|msg| io::Error::new(io::ErrorKind::InvalidInput, msg))?;
+
     file.seek(SeekFrom::Start(offset))?;
     file.write_all(data)?;
     file.flush()?;
@@ -160,3 +610,300 @@ fn write_mode(path: &PathBuf, offset: u64, data: &[u8]) -> io::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Temporary file holding `data`, removed on drop
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str, data: &[u8]) -> Self {
+            let path = std::env::temp_dir().join(format!("hextool-{}-{name}", std::process::id()));
+            std::fs::write(&path, data).unwrap();
+            TempFile(path)
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    fn dump_to_string(
+        file: &TempFile,
+        start: Position,
+        extent: Extent,
+        layout: &Layout,
+    ) -> io::Result<(String, Dumped)> {
+        let mut out = Vec::new();
+        let dumped = dump(&file.0, start, extent, layout, &mut out)?;
+        Ok((String::from_utf8(out).unwrap(), dumped))
+    }
+
+    #[test]
+    fn ranges() {
+        use Extent::*;
+        use Position::*;
+
+        assert_eq!(
+            parse_range("0x100..0x200"),
+            Ok((Start(0x100), Until(Start(0x200))))
+        );
+        assert_eq!(parse_range("0x100.."), Ok((Start(0x100), All)));
+        assert_eq!(parse_range("..0x10"), Ok((Start(0), Until(Start(0x10)))));
+        assert_eq!(parse_range("-64.."), Ok((End(64), All)));
+        assert_eq!(parse_range("-64..-0x10"), Ok((End(64), Until(End(0x10)))));
+        assert_eq!(parse_range("0x100+64"), Ok((Start(0x100), Bytes(64))));
+        assert!(parse_range("0x100").is_err());
+        assert!(parse_range("0x100+-4").is_err());
+        assert!(parse_range("zz..").is_err());
+
+        assert_eq!(parse_position("-0x10"), Ok(End(16)));
+        assert_eq!(parse_size("all"), Ok(All));
+        assert_eq!(parse_size("0x20"), Ok(Bytes(32)));
+        assert!(parse_size("everything").is_err());
+    }
+
+    #[test]
+    fn negative_positions() {
+        assert_eq!(Position::End(4).resolve(10), Ok(6));
+        assert_eq!(Position::End(10).resolve(10), Ok(0));
+        assert!(Position::End(11).resolve(10).is_err());
+        assert_eq!(Position::Start(99).resolve(10), Ok(99));
+
+        // -o accepte une valeur négative sans la prendre pour une option
+        let args =
+            Args::try_parse_from(["hextool", "-f", "x", "-r", "-o", "-64", "-s", "all"]).unwrap();
+        assert_eq!(parse_position(&args.offset), Ok(Position::End(64)));
+        assert_eq!(parse_size(&args.size), Ok(Extent::All));
+    }
+
+    #[test]
+    fn invalid_ranges_are_rejected() {
+        let file = TempFile::new("invalid", &[0u8; 32]);
+        let layout = Layout::preset(None);
+        let kind = |start, extent| {
+            dump_to_string(&file, start, extent, &layout)
+                .unwrap_err()
+                .kind()
+        };
+
+        // fin avant le début
+        assert_eq!(
+            kind(Position::Start(0x10), Extent::Until(Position::Start(0x8))),
+            io::ErrorKind::InvalidInput
+        );
+        // avant l'octet 0
+        assert_eq!(
+            kind(Position::End(33), Extent::All),
+            io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            kind(Position::Start(0), Extent::Until(Position::End(40))),
+            io::ErrorKind::InvalidInput
+        );
+        // après la fin du fichier
+        assert_eq!(
+            kind(Position::Start(33), Extent::All),
+            io::ErrorKind::InvalidInput
+        );
+    }
+
+    #[test]
+    fn reads_what_the_range_asks() {
+        let data: Vec<u8> = (0..40).collect();
+        let file = TempFile::new("extent", &data);
+        let layout = Layout::preset(None);
+
+        // --size all à partir de -4
+        let (text, dumped) = dump_to_string(&file, Position::End(4), Extent::All, &layout).unwrap();
+        assert_eq!(
+            text,
+            "00000024: 24 25 26 27                                     |$%&'|\n"
+        );
+        assert_eq!(
+            dumped,
+            Dumped {
+                read: 4,
+                wanted: None,
+                len: 40
+            }
+        );
+
+        // START..END, fin exclue
+        let (text, dumped) = dump_to_string(
+            &file,
+            Position::Start(0x10),
+            Extent::Until(Position::Start(0x12)),
+            &layout,
+        )
+        .unwrap();
+        assert!(text.starts_with("00000010: 10 11 "), "{text}");
+        assert_eq!(dumped.read, 2);
+
+        // taille demandée au-delà de la fin : lu jusqu'au bout, manque signalé
+        let (text, dumped) =
+            dump_to_string(&file, Position::Start(0x20), Extent::Bytes(64), &layout).unwrap();
+        assert_eq!(text.lines().count(), 1);
+        assert_eq!(
+            dumped,
+            Dumped {
+                read: 8,
+                wanted: Some(64),
+                len: 40
+            }
+        );
+
+        // offset exactement en fin de fichier : rien à afficher
+        let (text, dumped) =
+            dump_to_string(&file, Position::Start(40), Extent::Bytes(16), &layout).unwrap();
+        assert_eq!(text, "");
+        assert_eq!(dumped.read, 0);
+    }
+
+    /// Layout for the layout flags in `flags`
+    fn layout(flags: &[&str]) -> Result<Layout, String> {
+        let base = ["hextool", "-f", "x", "-r"];
+        let args = Args::try_parse_from(base.iter().chain(flags)).unwrap();
+        Layout::from_args(&args)
+    }
+
+    /// `Hello, hextool!\n`, three lines of zeros, then `tail`
+    fn sample() -> TempFile {
+        let mut data = b"Hello, hextool!\n".to_vec();
+        data.extend([0u8; 48]);
+        data.extend(b"tail");
+        TempFile::new("sample", &data)
+    }
+
+    fn line(layout: &Layout, offset: u64, chunk: &[u8]) -> String {
+        let mut out = Vec::new();
+        layout.write_line(&mut out, offset, chunk).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    // lignes de référence produites par `xxd` et `hexdump -C`
+
+    #[test]
+    fn xxd_preset() {
+        let (text, _) = dump_to_string(
+            &sample(),
+            Position::Start(0),
+            Extent::All,
+            &layout(&["--preset", "xxd"]).unwrap(),
+        )
+        .unwrap();
+        // xxd ne regroupe pas les lignes répétées et n'affiche pas d'offset final
+        assert_eq!(
+            text,
+            "\
+00000000: 4865 6c6c 6f2c 2068 6578 746f 6f6c 210a  Hello, hextool!.
+00000010: 0000 0000 0000 0000 0000 0000 0000 0000  ................
+00000020: 0000 0000 0000 0000 0000 0000 0000 0000  ................
+00000030: 0000 0000 0000 0000 0000 0000 0000 0000  ................
+00000040: 7461 696c                                tail
+"
+        );
+    }
+
+    #[test]
+    fn hexdump_preset() {
+        let (text, _) = dump_to_string(
+            &sample(),
+            Position::Start(0),
+            Extent::All,
+            &layout(&["--preset", "hexdump"]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            text,
+            "\
+00000000  48 65 6c 6c 6f 2c 20 68  65 78 74 6f 6f 6c 21 0a  |Hello, hextool!.|
+00000010  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|
+*
+00000040  74 61 69 6c                                       |tail|
+00000044
+"
+        );
+
+        // une série répétée jusqu'à la fin : l'étoile puis l'offset final
+        let zeros = TempFile::new("zeros", &[0u8; 64]);
+        let (text, _) = dump_to_string(
+            &zeros,
+            Position::Start(0),
+            Extent::All,
+            &layout(&["--preset", "hexdump"]).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(
+            text,
+            "\
+00000000  00 00 00 00 00 00 00 00  00 00 00 00 00 00 00 00  |................|
+*
+00000040
+"
+        );
+    }
+
+    #[test]
+    fn partial_groups_follow_the_byte_order() {
+        // comme `xxd -g 4` et `xxd -e -g 4`
+        let big = layout(&["--preset", "xxd", "--group", "4"]).unwrap();
+        assert_eq!(
+            line(&big, 0, b"ABCDEF"),
+            "00000000: 41424344 4546                        ABCDEF\n"
+        );
+        let little = layout(&["--preset", "xxd", "--group", "4", "--endian", "little"]).unwrap();
+        assert_eq!(
+            line(&little, 0, b"ABCDEF"),
+            "00000000: 44434241     4645                    ABCDEF\n"
+        );
+    }
+
+    #[test]
+    fn bases() {
+        let with = |base| layout(&["--cols", "4", "--base", base]).unwrap();
+        assert_eq!(
+            line(&with("dec"), 0, b"AB\xff"),
+            "00000000: 065 066 255     |AB.|\n"
+        );
+        assert_eq!(
+            line(&with("oct"), 0, b"AB\xff"),
+            "00000000: 101 102 377     |AB.|\n"
+        );
+        // comme `xxd -b -c 4`
+        assert_eq!(
+            line(&with("bin"), 4, b"EF"),
+            "00000004: 01000101 01000110                   |EF|\n"
+        );
+        // un groupe de 2 octets en décimal tient sur 5 chiffres ; un groupe
+        // incomplet garde la largeur de ses octets, comme en hexadécimal
+        let dec = layout(&["--cols", "4", "--group", "2", "--base", "dec"]).unwrap();
+        assert_eq!(line(&dec, 0, b"AB\x01"), "00000000: 16706 001   |AB.|\n");
+        assert_eq!(
+            line(&layout(&["--upper"]).unwrap(), 0xab, b"\xab"),
+            format!("000000ab: AB {:45}|.|\n", "")
+        );
+    }
+
+    #[test]
+    fn invalid_layouts_are_rejected() {
+        assert!(layout(&["--group", "3"]).is_err());
+        assert!(layout(&["--group", "16"]).is_err());
+        assert!(layout(&["--cols", "0"]).is_err());
+        assert!(layout(&["--cols", "257"]).is_err());
+        // pas un multiple de la taille de groupe, y compris celle du preset
+        assert!(layout(&["--cols", "6", "--group", "4"]).is_err());
+        assert!(layout(&["--preset", "xxd", "--cols", "5"]).is_err());
+        assert!(layout(&["--cols", "256", "--group", "8"]).is_ok());
+        // un nombre impair de groupes : pas de séparateur du milieu
+        assert!(
+            !layout(&["--preset", "hexdump", "--cols", "6", "--group", "2"])
+                .unwrap()
+                .split_half
+        );
+    }
+}